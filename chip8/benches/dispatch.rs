@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use chip8::Chip8;
+
+// A loop touching most instruction families: ALU ops, skips, memory
+// transfers, BCD and a draw per iteration.
+#[rustfmt::skip]
+const ROM: &[u8] = &[
+    0x60, 0x05, // 200: LD V0, 0x05
+    0x61, 0x03, // 202: LD V1, 0x03
+    0x80, 0x14, // 204: ADD V0, V1
+    0x80, 0x15, // 206: SUB V0, V1
+    0x80, 0x16, // 208: SHR V0, V1
+    0x80, 0x1E, // 20A: SHL V0, V1
+    0x82, 0x01, // 20C: LD V2, V0
+    0x82, 0x11, // 20E: OR V2, V1
+    0x82, 0x12, // 210: AND V2, V1
+    0x82, 0x13, // 212: XOR V2, V1
+    0x30, 0x00, // 214: SE V0, 0x00
+    0x40, 0x00, // 216: SNE V0, 0x00
+    0x50, 0x10, // 218: SE V0, V1
+    0x90, 0x10, // 21A: SNE V0, V1
+    0xA3, 0x00, // 21C: LD I, 0x300
+    0xF2, 0x33, // 21E: LD B, V2
+    0xF2, 0x65, // 220: LD V2, [I]
+    0xF0, 0x29, // 222: LD F, V0
+    0xD0, 0x15, // 224: DRW V0, V1, 5
+    0xE0, 0x9E, // 226: SKP V0
+    0xF3, 0x07, // 228: LD V3, DT
+    0x12, 0x04, // 22A: JP 0x204
+];
+
+fn opcodes() -> impl Iterator<Item = u16> {
+    (0..=0xFFFF).step_by(7)
+}
+
+fn bench_decode(c: &mut Criterion) {
+    c.bench_function("decode", |b| {
+        b.iter(|| {
+            for opcode in opcodes() {
+                black_box(chip8::decode(black_box(opcode)));
+            }
+        })
+    });
+}
+
+fn bench_cycle(c: &mut Criterion) {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_from_bytes(ROM).unwrap();
+    c.bench_function("cycle", |b| {
+        b.iter(|| {
+            for _ in 0..1000 {
+                black_box(chip8.cycle().unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_decode, bench_cycle);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+use chip8::{Chip8, DISPLAY_WIDTH};
+
+const DEFAULT_CYCLES: u64 = 10_000;
+
+// Usage: cargo run --example headless <rom> [cycles]
+fn main() -> chip8::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().expect("No ROM path is provided.");
+    let cycles = args
+        .next()
+        .map(|n| n.parse().expect("Cycle count must be a number."))
+        .unwrap_or(DEFAULT_CYCLES);
+
+    let mut chip8 = Chip8::new();
+    chip8.load(path)?;
+
+    // Below 60 Hz there is less than one cycle per frame; tick after each.
+    let cycles_per_frame = chip8.cycles_per_frame().max(1) as u64;
+    for i in 0..cycles {
+        if chip8.is_halted() {
+            break;
+        }
+        chip8.cycle()?;
+        if (i + 1) % cycles_per_frame == 0 {
+            chip8.timer();
+        }
+    }
+
+    let mut screen = String::new();
+    for (x, _, on) in chip8.pixels() {
+        screen.push(if on { '#' } else { '.' });
+        if x == DISPLAY_WIDTH - 1 {
+            screen.push('\n');
+        }
+    }
+    print!("{screen}");
+    Ok(())
+}
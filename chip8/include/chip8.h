@@ -0,0 +1,36 @@
+#ifndef CHIP8_H
+#define CHIP8_H
+
+/* C interface to the chip8 crate, built with `--features ffi`. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct Chip8 Chip8;
+
+Chip8 *chip8_new(void);
+void chip8_free(Chip8 *chip8);
+
+bool chip8_load(Chip8 *chip8, const uint8_t *rom, size_t len);
+bool chip8_cycle(Chip8 *chip8);
+void chip8_timer(Chip8 *chip8);
+
+/* chip8_display_size() pixels in row-major order, 64 per row. chip8_display
+ * returns NULL in builds with the bitset-display feature; chip8_copy_display
+ * works in every build. */
+const bool *chip8_display(const Chip8 *chip8);
+bool chip8_copy_display(const Chip8 *chip8, bool *out, size_t len);
+size_t chip8_display_size(void);
+
+void chip8_set_key(Chip8 *chip8, uint8_t key, bool pressed);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,12 @@
+; Shown when a frontend starts without a ROM: "C8" in the font sprites,
+; centred on the display. Assemble with chip8_asm to rebuild splash.ch8.
+        LD V0, 0x0C
+        LD F, V0
+        LD V1, 26
+        LD V2, 13
+        DRW V1, V2, 5
+        LD V0, 0x08
+        LD F, V0
+        ADD V1, 7
+        DRW V1, V2, 5
+idle:   JP idle
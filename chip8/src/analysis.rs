@@ -0,0 +1,32 @@
+use crate::instruction::{decode, Instruction};
+
+// Result of a linear pass over a ROM as loaded at 0x200. Data embedded in the
+// program is counted like code, so the numbers are estimates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomAnalysis {
+    // Words per opcode family, indexed by the high nibble.
+    pub families: [u64; 0x10],
+    // Addresses of words that would fail with `Error::UnknownOpcode`.
+    pub unknown: Vec<u16>,
+}
+
+impl RomAnalysis {
+    pub fn total(&self) -> u64 {
+        self.families.iter().sum()
+    }
+}
+
+pub fn analyze(rom: &[u8]) -> RomAnalysis {
+    let mut analysis = RomAnalysis {
+        families: [0; 0x10],
+        unknown: Vec::new(),
+    };
+    for (i, word) in rom.chunks_exact(2).enumerate() {
+        let opcode = u16::from_be_bytes([word[0], word[1]]);
+        analysis.families[(opcode >> 12) as usize] += 1;
+        if let Instruction::Unknown(_) = decode(opcode) {
+            analysis.unknown.push(0x200 + i as u16 * 2);
+        }
+    }
+    analysis
+}
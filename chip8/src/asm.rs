@@ -0,0 +1,335 @@
+// Encoders for the standard instruction set, returning big-endian opcode
+// bytes ready to be concatenated into a ROM image.
+
+use std::collections::HashMap;
+
+const fn op(opcode: u16) -> [u8; 2] {
+    opcode.to_be_bytes()
+}
+
+const fn xkk(o: u16, x: u8, kk: u8) -> [u8; 2] {
+    op(o << 12 | (x as u16 & 0xF) << 8 | kk as u16)
+}
+
+const fn xyn(o: u16, x: u8, y: u8, n: u8) -> [u8; 2] {
+    op(o << 12 | (x as u16 & 0xF) << 8 | (y as u16 & 0xF) << 4 | (n as u16 & 0xF))
+}
+
+const fn nnn(o: u16, addr: u16) -> [u8; 2] {
+    op(o << 12 | (addr & 0xFFF))
+}
+
+pub const fn cls() -> [u8; 2] {
+    op(0x00E0)
+}
+
+pub const fn ret() -> [u8; 2] {
+    op(0x00EE)
+}
+
+pub const fn exit() -> [u8; 2] {
+    op(0x00FD)
+}
+
+pub const fn sys(addr: u16) -> [u8; 2] {
+    nnn(0x0, addr)
+}
+
+pub const fn jp(addr: u16) -> [u8; 2] {
+    nnn(0x1, addr)
+}
+
+pub const fn call(addr: u16) -> [u8; 2] {
+    nnn(0x2, addr)
+}
+
+pub const fn se_vx_byte(x: u8, kk: u8) -> [u8; 2] {
+    xkk(0x3, x, kk)
+}
+
+pub const fn sne_vx_byte(x: u8, kk: u8) -> [u8; 2] {
+    xkk(0x4, x, kk)
+}
+
+pub const fn se_vx_vy(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x5, x, y, 0)
+}
+
+pub const fn ld_vx_byte(x: u8, kk: u8) -> [u8; 2] {
+    xkk(0x6, x, kk)
+}
+
+pub const fn add_vx_byte(x: u8, kk: u8) -> [u8; 2] {
+    xkk(0x7, x, kk)
+}
+
+pub const fn ld_vx_vy(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x8, x, y, 0x0)
+}
+
+pub const fn or(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x8, x, y, 0x1)
+}
+
+pub const fn and(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x8, x, y, 0x2)
+}
+
+pub const fn xor(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x8, x, y, 0x3)
+}
+
+pub const fn add_vx_vy(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x8, x, y, 0x4)
+}
+
+pub const fn sub(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x8, x, y, 0x5)
+}
+
+pub const fn shr(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x8, x, y, 0x6)
+}
+
+pub const fn subn(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x8, x, y, 0x7)
+}
+
+pub const fn shl(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x8, x, y, 0xE)
+}
+
+pub const fn sne_vx_vy(x: u8, y: u8) -> [u8; 2] {
+    xyn(0x9, x, y, 0)
+}
+
+pub const fn ld_i(addr: u16) -> [u8; 2] {
+    nnn(0xA, addr)
+}
+
+pub const fn jp_v0(addr: u16) -> [u8; 2] {
+    nnn(0xB, addr)
+}
+
+pub const fn rnd(x: u8, kk: u8) -> [u8; 2] {
+    xkk(0xC, x, kk)
+}
+
+pub const fn draw(x: u8, y: u8, n: u8) -> [u8; 2] {
+    xyn(0xD, x, y, n)
+}
+
+pub const fn skp(x: u8) -> [u8; 2] {
+    xkk(0xE, x, 0x9E)
+}
+
+pub const fn sknp(x: u8) -> [u8; 2] {
+    xkk(0xE, x, 0xA1)
+}
+
+pub const fn ld_vx_dt(x: u8) -> [u8; 2] {
+    xkk(0xF, x, 0x07)
+}
+
+pub const fn ld_vx_k(x: u8) -> [u8; 2] {
+    xkk(0xF, x, 0x0A)
+}
+
+pub const fn ld_dt_vx(x: u8) -> [u8; 2] {
+    xkk(0xF, x, 0x15)
+}
+
+pub const fn ld_st_vx(x: u8) -> [u8; 2] {
+    xkk(0xF, x, 0x18)
+}
+
+pub const fn add_i_vx(x: u8) -> [u8; 2] {
+    xkk(0xF, x, 0x1E)
+}
+
+pub const fn ld_f_vx(x: u8) -> [u8; 2] {
+    xkk(0xF, x, 0x29)
+}
+
+pub const fn ld_b_vx(x: u8) -> [u8; 2] {
+    xkk(0xF, x, 0x33)
+}
+
+pub const fn ld_i_vx(x: u8) -> [u8; 2] {
+    xkk(0xF, x, 0x55)
+}
+
+pub const fn ld_vx_i(x: u8) -> [u8; 2] {
+    xkk(0xF, x, 0x65)
+}
+
+// Concatenates encoded instructions into a ROM image.
+pub fn program(instructions: &[[u8; 2]]) -> Vec<u8> {
+    instructions.concat()
+}
+
+enum Operand<'a> {
+    V(u8),
+    I,
+    IndirectI,
+    Dt,
+    St,
+    K,
+    F,
+    B,
+    Value(&'a str),
+}
+
+impl<'a> Operand<'a> {
+    fn parse(s: &'a str) -> Self {
+        match s.to_ascii_uppercase().as_str() {
+            "I" => return Operand::I,
+            "[I]" => return Operand::IndirectI,
+            "DT" => return Operand::Dt,
+            "ST" => return Operand::St,
+            "K" => return Operand::K,
+            "F" => return Operand::F,
+            "B" => return Operand::B,
+            _ => {}
+        }
+        match s.as_bytes() {
+            [b'V' | b'v', digit] if digit.is_ascii_hexdigit() => {
+                Operand::V((*digit as char).to_digit(16).unwrap() as u8)
+            }
+            _ => Operand::Value(s),
+        }
+    }
+}
+
+// Assembles source in the syntax `disassemble` prints, loaded at 0x200. Each
+// line holds an optional `label:`, then an instruction or a `DB` directive
+// listing bytes. Numbers are decimal or 0x-prefixed hex, and address operands
+// may name a label. `;` starts a comment.
+pub fn assemble(source: &str) -> crate::Result<Vec<u8>> {
+    let mut labels = HashMap::new();
+    let mut lines = Vec::new();
+    let mut addr = 0x200u16;
+    for (i, line) in source.lines().enumerate() {
+        let line_no = i + 1;
+        let mut text = line.split(';').next().unwrap_or("").trim();
+        if let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if label.is_empty() || label.contains(char::is_whitespace) {
+                return Err(error(line_no, format!("invalid label `{label}`")));
+            }
+            if labels.insert(label, addr).is_some() {
+                return Err(error(line_no, format!("duplicate label `{label}`")));
+            }
+            text = rest.trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let operands: Vec<&str> = if operands.trim().is_empty() {
+            Vec::new()
+        } else {
+            operands.split(',').map(str::trim).collect()
+        };
+        let mnemonic = mnemonic.to_ascii_uppercase();
+        let size = if mnemonic == "DB" { operands.len() } else { 2 };
+        lines.push((line_no, mnemonic, operands));
+        addr = addr.wrapping_add(size as u16);
+    }
+
+    let mut rom = Vec::new();
+    for (line_no, mnemonic, operands) in &lines {
+        let bytes = encode(mnemonic, operands, &labels).map_err(|e| error(*line_no, e))?;
+        rom.extend_from_slice(&bytes);
+    }
+    Ok(rom)
+}
+
+fn error(line: usize, message: String) -> crate::Error {
+    crate::Error::Assemble(line, message)
+}
+
+fn encode(
+    mnemonic: &str,
+    operands: &[&str],
+    labels: &HashMap<&str, u16>,
+) -> Result<Vec<u8>, String> {
+    use Operand::*;
+
+    if mnemonic == "DB" {
+        return operands.iter().map(|op| byte(op)).collect();
+    }
+
+    let operands: Vec<Operand> = operands.iter().map(|op| Operand::parse(op)).collect();
+    let word = match (mnemonic, operands.as_slice()) {
+        ("CLS", []) => cls(),
+        ("RET", []) => ret(),
+        ("EXIT", []) => exit(),
+        ("SYS", [Value(a)]) => sys(address(a, labels)?),
+        ("JP", [Value(a)]) => jp(address(a, labels)?),
+        ("JP", [V(0), Value(a)]) => jp_v0(address(a, labels)?),
+        ("CALL", [Value(a)]) => call(address(a, labels)?),
+        ("SE", [V(x), V(y)]) => se_vx_vy(*x, *y),
+        ("SE", [V(x), Value(kk)]) => se_vx_byte(*x, byte(kk)?),
+        ("SNE", [V(x), V(y)]) => sne_vx_vy(*x, *y),
+        ("SNE", [V(x), Value(kk)]) => sne_vx_byte(*x, byte(kk)?),
+        ("LD", [V(x), V(y)]) => ld_vx_vy(*x, *y),
+        ("LD", [V(x), Value(kk)]) => ld_vx_byte(*x, byte(kk)?),
+        ("LD", [I, Value(a)]) => ld_i(address(a, labels)?),
+        ("LD", [V(x), Dt]) => ld_vx_dt(*x),
+        ("LD", [V(x), K]) => ld_vx_k(*x),
+        ("LD", [Dt, V(x)]) => ld_dt_vx(*x),
+        ("LD", [St, V(x)]) => ld_st_vx(*x),
+        ("LD", [F, V(x)]) => ld_f_vx(*x),
+        ("LD", [B, V(x)]) => ld_b_vx(*x),
+        ("LD", [IndirectI, V(x)]) => ld_i_vx(*x),
+        ("LD", [V(x), IndirectI]) => ld_vx_i(*x),
+        ("ADD", [V(x), V(y)]) => add_vx_vy(*x, *y),
+        ("ADD", [V(x), Value(kk)]) => add_vx_byte(*x, byte(kk)?),
+        ("ADD", [I, V(x)]) => add_i_vx(*x),
+        ("OR", [V(x), V(y)]) => or(*x, *y),
+        ("AND", [V(x), V(y)]) => and(*x, *y),
+        ("XOR", [V(x), V(y)]) => xor(*x, *y),
+        ("SUB", [V(x), V(y)]) => sub(*x, *y),
+        ("SUBN", [V(x), V(y)]) => subn(*x, *y),
+        ("SHR", [V(x)]) => shr(*x, *x),
+        ("SHR", [V(x), V(y)]) => shr(*x, *y),
+        ("SHL", [V(x)]) => shl(*x, *x),
+        ("SHL", [V(x), V(y)]) => shl(*x, *y),
+        ("RND", [V(x), Value(kk)]) => rnd(*x, byte(kk)?),
+        ("DRW", [V(x), V(y), Value(n)]) => draw(*x, *y, nibble(n)?),
+        ("SKP", [V(x)]) => skp(*x),
+        ("SKNP", [V(x)]) => sknp(*x),
+        _ => return Err(format!("unrecognized instruction `{mnemonic}`")),
+    };
+    Ok(word.to_vec())
+}
+
+fn number(s: &str) -> Option<u16> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+fn address(s: &str, labels: &HashMap<&str, u16>) -> Result<u16, String> {
+    match number(s).or_else(|| labels.get(s).copied()) {
+        Some(addr) if addr <= 0xFFF => Ok(addr),
+        Some(addr) => Err(format!("address {addr:#X} is out of range")),
+        None => Err(format!("unknown label `{s}`")),
+    }
+}
+
+fn byte(s: &str) -> Result<u8, String> {
+    number(s)
+        .and_then(|n| u8::try_from(n).ok())
+        .ok_or_else(|| format!("expected a byte, found `{s}`"))
+}
+
+fn nibble(s: &str) -> Result<u8, String> {
+    number(s)
+        .filter(|n| *n <= 0xF)
+        .map(|n| n as u8)
+        .ok_or_else(|| format!("expected a nibble, found `{s}`"))
+}
@@ -0,0 +1,31 @@
+// Pitch of the beep; the original hardware had a single fixed tone.
+pub(crate) const BEEP_FREQ: f32 = 440.0;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    // The hard-edged tone of the COSMAC VIP buzzer.
+    #[default]
+    Square,
+    Sine,
+    Triangle,
+    Sawtooth,
+}
+
+impl Waveform {
+    // Amplitude in [-1, 1] at `phase`, the position within one period in
+    // [0, 1).
+    pub(crate) fn sample(self, phase: f32) -> f32 {
+        match self {
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Sine => (phase * std::f32::consts::TAU).sin(),
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Sawtooth => 2.0 * phase - 1.0,
+        }
+    }
+}
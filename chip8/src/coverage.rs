@@ -0,0 +1,44 @@
+// Process-wide record of which opcode families (the high nibble) any Chip8
+// has executed, so a test run can fail when an instruction goes untested.
+
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Mutex;
+
+static FAMILIES: AtomicU16 = AtomicU16::new(0);
+static REPORT: Mutex<()> = Mutex::new(());
+
+// When set, the families seen so far are written to this file as a hex mask
+// each time a new one turns up, so a parent process can collect coverage
+// from a whole test run.
+pub const REPORT_VAR: &str = "CHIP8_COVERAGE_REPORT";
+
+pub(crate) fn record(family: u16) {
+    let bit = 1 << family;
+    if FAMILIES.fetch_or(bit, Ordering::Relaxed) & bit != 0 {
+        return;
+    }
+    if let Some(path) = std::env::var_os(REPORT_VAR) {
+        // Reload under the lock so a slower writer can't overwrite a newer
+        // mask with an older one.
+        let _guard = REPORT.lock().unwrap_or_else(|e| e.into_inner());
+        let seen = FAMILIES.load(Ordering::Relaxed);
+        // Coverage is best effort; a failed write shows up as missing
+        // families.
+        let _ = std::fs::write(path, format!("{seen:04X}"));
+    }
+}
+
+// Families with no executed instruction, e.g. [0xB, 0xE].
+pub fn uncovered_families() -> Vec<u8> {
+    missing_from(FAMILIES.load(Ordering::Relaxed))
+}
+
+// Families absent from a report written through `REPORT_VAR`. An empty or
+// unreadable report counts as covering nothing.
+pub fn uncovered_in_report(report: &str) -> Vec<u8> {
+    missing_from(u16::from_str_radix(report.trim(), 16).unwrap_or(0))
+}
+
+fn missing_from(seen: u16) -> Vec<u8> {
+    (0..16).filter(|family| seen & (1 << family) == 0).collect()
+}
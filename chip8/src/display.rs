@@ -0,0 +1,144 @@
+// The framebuffer behind Chip8's pixel API. Pixels are addressed by their
+// row-major index, as in DISPLAY_SIZE. The default stores one bool per
+// pixel; with the bitset-display feature each row is packed into a u64 with
+// x = 0 in the most significant bit. Both are always compiled so they can be
+// tested against each other.
+
+use crate::{DISPLAY_HEIGHT, DISPLAY_SIZE, DISPLAY_WIDTH};
+
+#[cfg(not(feature = "bitset-display"))]
+pub(crate) type Framebuffer = BoolFramebuffer;
+#[cfg(feature = "bitset-display")]
+pub(crate) type Framebuffer = BitsetFramebuffer;
+
+pub(crate) trait Pixels {
+    fn get(&self, index: usize) -> bool;
+    fn set(&mut self, index: usize, on: bool);
+    fn clear(&mut self);
+
+    fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..DISPLAY_SIZE).map(|index| self.get(index))
+    }
+
+    fn to_array(&self) -> [bool; DISPLAY_SIZE] {
+        let mut pixels = [false; DISPLAY_SIZE];
+        for (pixel, on) in pixels.iter_mut().zip(self.iter()) {
+            *pixel = on;
+        }
+        pixels
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct BoolFramebuffer([bool; DISPLAY_SIZE]);
+
+#[cfg_attr(feature = "bitset-display", allow(dead_code))]
+impl BoolFramebuffer {
+    pub(crate) const fn new() -> Self {
+        BoolFramebuffer([false; DISPLAY_SIZE])
+    }
+
+    // For chip8_display, which hands C a pointer into the storage.
+    #[cfg(feature = "ffi")]
+    pub(crate) fn as_array(&self) -> &[bool; DISPLAY_SIZE] {
+        &self.0
+    }
+}
+
+impl Pixels for BoolFramebuffer {
+    fn get(&self, index: usize) -> bool {
+        self.0[index]
+    }
+
+    fn set(&mut self, index: usize, on: bool) {
+        self.0[index] = on;
+    }
+
+    fn clear(&mut self) {
+        self.0.fill(false);
+    }
+
+    fn to_array(&self) -> [bool; DISPLAY_SIZE] {
+        self.0
+    }
+}
+
+const _: () = assert!(DISPLAY_WIDTH == u64::BITS as usize);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct BitsetFramebuffer([u64; DISPLAY_HEIGHT]);
+
+#[cfg_attr(not(feature = "bitset-display"), allow(dead_code))]
+impl BitsetFramebuffer {
+    pub(crate) const fn new() -> Self {
+        BitsetFramebuffer([0; DISPLAY_HEIGHT])
+    }
+
+    fn mask(index: usize) -> u64 {
+        1 << (DISPLAY_WIDTH - 1 - index % DISPLAY_WIDTH)
+    }
+}
+
+impl Pixels for BitsetFramebuffer {
+    fn get(&self, index: usize) -> bool {
+        self.0[index / DISPLAY_WIDTH] & Self::mask(index) != 0
+    }
+
+    fn set(&mut self, index: usize, on: bool) {
+        let row = &mut self.0[index / DISPLAY_WIDTH];
+        if on {
+            *row |= Self::mask(index);
+        } else {
+            *row &= !Self::mask(index);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.0.fill(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // XORs an 8-pixel-wide sprite in at (x, y), wrapping like Dxyn.
+    fn draw(fb: &mut impl Pixels, x: usize, y: usize, sprite: &[u8]) {
+        for (row, byte) in sprite.iter().enumerate() {
+            for bit in 0..8 {
+                if byte & (0x80 >> bit) != 0 {
+                    let px = (x + bit) % DISPLAY_WIDTH;
+                    let py = (y + row) % DISPLAY_HEIGHT;
+                    let index = px + DISPLAY_WIDTH * py;
+                    fb.set(index, !fb.get(index));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bool_and_bitset_framebuffers_agree() {
+        let mut bools = BoolFramebuffer::new();
+        let mut bits = BitsetFramebuffer::new();
+        let draws: [(usize, usize, &[u8]); 5] = [
+            (0, 0, &[0xF0, 0x90, 0x90, 0x90, 0xF0]),
+            (2, 2, &[0xFF, 0x81, 0xFF]),
+            // Straddles the right edge and the bottom.
+            (60, 30, &[0xAA, 0x55, 0xAA]),
+            (63, 0, &[0xFF; 15]),
+            (0, 0, &[0xF0, 0x90, 0x90, 0x90, 0xF0]),
+        ];
+        for (x, y, sprite) in draws {
+            draw(&mut bools, x, y, sprite);
+            draw(&mut bits, x, y, sprite);
+            assert_eq!(bools.to_array(), bits.to_array(), "after ({x}, {y})");
+        }
+        assert!(bools.iter().eq(bits.iter()));
+        assert!(bits.iter().any(|on| on));
+
+        bools.clear();
+        bits.clear();
+        assert_eq!(bools.to_array(), bits.to_array());
+        assert!(!bits.iter().any(|on| on));
+    }
+}
@@ -0,0 +1,96 @@
+// C interface over an opaque Chip8 handle, declared in include/chip8.h.
+// Every function accepts a null handle and does nothing with it. Other
+// pointers must be valid for the stated length, and a handle must come from
+// chip8_new and not be used after chip8_free.
+#![allow(clippy::missing_safety_doc)]
+
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{Chip8, DISPLAY_SIZE};
+
+#[no_mangle]
+pub extern "C" fn chip8_new() -> *mut Chip8 {
+    Box::into_raw(Box::new(Chip8::new()))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chip8_free(chip8: *mut Chip8) {
+    if !chip8.is_null() {
+        drop(Box::from_raw(chip8));
+    }
+}
+
+// Loads `len` bytes from `rom` at 0x200. Returns false if the ROM is null or
+// does not fit.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_load(chip8: *mut Chip8, rom: *const u8, len: usize) -> bool {
+    let (Some(chip8), false) = (chip8.as_mut(), rom.is_null()) else {
+        return false;
+    };
+    chip8
+        .load_from_bytes(std::slice::from_raw_parts(rom, len))
+        .is_ok()
+}
+
+// Returns false once the program hits an error; the machine state is left as
+// it was when the error happened. A panic in the core counts as an error too,
+// since unwinding into C would abort the host.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_cycle(chip8: *mut Chip8) -> bool {
+    chip8.as_mut().is_some_and(|chip8| {
+        panic::catch_unwind(AssertUnwindSafe(|| chip8.cycle().is_ok())).unwrap_or(false)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chip8_timer(chip8: *mut Chip8) {
+    if let Some(chip8) = chip8.as_mut() {
+        chip8.timer();
+    }
+}
+
+// DISPLAY_SIZE pixels in row-major order, valid until the next call that
+// takes the handle mutably. Always null with the bitset-display feature,
+// which has no bool array to point into; use chip8_copy_display there.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_display(chip8: *const Chip8) -> *const bool {
+    match chip8.as_ref() {
+        #[cfg(not(feature = "bitset-display"))]
+        Some(chip8) => chip8.display.as_array().as_ptr(),
+        _ => std::ptr::null(),
+    }
+}
+
+// Copies the display into `out`, which must hold `len` bools. Returns false
+// unless `len` is exactly DISPLAY_SIZE.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_copy_display(
+    chip8: *const Chip8,
+    out: *mut bool,
+    len: usize,
+) -> bool {
+    let Some(chip8) = chip8.as_ref() else {
+        return false;
+    };
+    if out.is_null() || len != DISPLAY_SIZE {
+        return false;
+    }
+    chip8.copy_display_into(std::slice::from_raw_parts_mut(out, len));
+    true
+}
+
+#[no_mangle]
+pub extern "C" fn chip8_display_size() -> usize {
+    DISPLAY_SIZE
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chip8_set_key(chip8: *mut Chip8, key: u8, pressed: bool) {
+    if let Some(chip8) = chip8.as_mut() {
+        if pressed {
+            chip8.press_key(key);
+        } else {
+            chip8.release_key(key);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt;
+
+use crate::SymbolMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    // 00E0 - CLS
+    Cls,
+    // 00EE - RET
+    Ret,
+    // 00FD - EXIT
+    Exit,
+    // 0nnn - SYS addr
+    Sys(u16),
+    // 1nnn - JP addr
+    Jp(u16),
+    // 2nnn - CALL addr
+    Call(u16),
+    // 3xkk - SE Vx, byte
+    SeByte { x: u8, kk: u8 },
+    // 4xkk - SNE Vx, byte
+    SneByte { x: u8, kk: u8 },
+    // 5xy0 - SE Vx, Vy
+    SeReg { x: u8, y: u8 },
+    // 6xkk - LD Vx, byte
+    LdByte { x: u8, kk: u8 },
+    // 7xkk - ADD Vx, byte
+    AddByte { x: u8, kk: u8 },
+    // 8xy0 - LD Vx, Vy
+    LdReg { x: u8, y: u8 },
+    // 8xy1 - OR Vx, Vy
+    Or { x: u8, y: u8 },
+    // 8xy2 - AND Vx, Vy
+    And { x: u8, y: u8 },
+    // 8xy3 - XOR Vx, Vy
+    Xor { x: u8, y: u8 },
+    // 8xy4 - ADD Vx, Vy
+    AddReg { x: u8, y: u8 },
+    // 8xy5 - SUB Vx, Vy
+    Sub { x: u8, y: u8 },
+    // 8xy6 - SHR Vx {, Vy}
+    Shr { x: u8, y: u8 },
+    // 8xy7 - SUBN Vx, Vy
+    Subn { x: u8, y: u8 },
+    // 8xyE - SHL Vx {, Vy}
+    Shl { x: u8, y: u8 },
+    // 9xy0 - SNE Vx, Vy
+    SneReg { x: u8, y: u8 },
+    // Annn - LD I, addr
+    LdI(u16),
+    // Bnnn - JP V0, addr
+    JpV0(u16),
+    // Cxkk - RND Vx, byte
+    Rnd { x: u8, kk: u8 },
+    // Dxyn - DRW Vx, Vy, nibble
+    Drw { x: u8, y: u8, n: u8 },
+    // Ex9E - SKP Vx
+    Skp { x: u8 },
+    // ExA1 - SKNP Vx
+    Sknp { x: u8 },
+    // Fx07 - LD Vx, DT
+    LdVxDt { x: u8 },
+    // Fx0A - LD Vx, K
+    LdVxK { x: u8 },
+    // Fx15 - LD DT, Vx
+    LdDtVx { x: u8 },
+    // Fx18 - LD ST, Vx
+    LdStVx { x: u8 },
+    // Fx1E - ADD I, Vx
+    AddIVx { x: u8 },
+    // Fx29 - LD F, Vx
+    LdFVx { x: u8 },
+    // Fx33 - LD B, Vx
+    LdBVx { x: u8 },
+    // Fx55 - LD [I], Vx
+    LdIVx { x: u8 },
+    // Fx65 - LD Vx, [I]
+    LdVxI { x: u8 },
+    Unknown(u16),
+}
+
+pub fn decode(opcode: u16) -> Instruction {
+    let o = (opcode & 0xF000) >> 12;
+    let nnn = opcode & 0x0FFF;
+    let n = (opcode & 0x000F) as u8;
+    let x = ((opcode & 0x0F00) >> 8) as u8;
+    let y = ((opcode & 0x00F0) >> 4) as u8;
+    let kk = (opcode & 0x00FF) as u8;
+
+    // Dispatch on the high nibble first so the outer match is a dense jump
+    // table; only the 0, 8, E and F families look at the low bits.
+    match o {
+        0x0 => match kk {
+            0xE0 => Instruction::Cls,
+            0xEE => Instruction::Ret,
+            0xFD => Instruction::Exit,
+            _ => Instruction::Sys(nnn),
+        },
+        0x1 => Instruction::Jp(nnn),
+        0x2 => Instruction::Call(nnn),
+        0x3 => Instruction::SeByte { x, kk },
+        0x4 => Instruction::SneByte { x, kk },
+        0x5 if n == 0 => Instruction::SeReg { x, y },
+        0x6 => Instruction::LdByte { x, kk },
+        0x7 => Instruction::AddByte { x, kk },
+        0x8 => match n {
+            0x0 => Instruction::LdReg { x, y },
+            0x1 => Instruction::Or { x, y },
+            0x2 => Instruction::And { x, y },
+            0x3 => Instruction::Xor { x, y },
+            0x4 => Instruction::AddReg { x, y },
+            0x5 => Instruction::Sub { x, y },
+            0x6 => Instruction::Shr { x, y },
+            0x7 => Instruction::Subn { x, y },
+            0xE => Instruction::Shl { x, y },
+            _ => Instruction::Unknown(opcode),
+        },
+        0x9 if n == 0 => Instruction::SneReg { x, y },
+        0xA => Instruction::LdI(nnn),
+        0xB => Instruction::JpV0(nnn),
+        0xC => Instruction::Rnd { x, kk },
+        0xD => Instruction::Drw { x, y, n },
+        0xE => match kk {
+            0x9E => Instruction::Skp { x },
+            0xA1 => Instruction::Sknp { x },
+            _ => Instruction::Unknown(opcode),
+        },
+        0xF => match kk {
+            0x07 => Instruction::LdVxDt { x },
+            0x0A => Instruction::LdVxK { x },
+            0x15 => Instruction::LdDtVx { x },
+            0x18 => Instruction::LdStVx { x },
+            0x1E => Instruction::AddIVx { x },
+            0x29 => Instruction::LdFVx { x },
+            0x33 => Instruction::LdBVx { x },
+            0x55 => Instruction::LdIVx { x },
+            0x65 => Instruction::LdVxI { x },
+            _ => Instruction::Unknown(opcode),
+        },
+        _ => Instruction::Unknown(opcode),
+    }
+}
+
+// Linear listing of `rom` as loaded at `origin`, one `ADDR  OPCODE  MNEMONIC`
+// line per word. Data embedded in the program is decoded like code.
+pub fn disassemble(rom: &[u8], origin: u16) -> String {
+    disassemble_with_symbols(rom, origin, &SymbolMap::default())
+}
+
+// Like `disassemble`, with a `NAME:` line before each labeled address and
+// address operands annotated with their label.
+pub fn disassemble_with_symbols(rom: &[u8], origin: u16, symbols: &SymbolMap) -> String {
+    let mut listing = String::new();
+    for (i, word) in rom.chunks(2).enumerate() {
+        let addr = origin as usize + i * 2;
+        if let Some(name) = symbols.get(addr as u16) {
+            listing.push_str(&format!("{name}:\n"));
+        }
+        let line = match *word {
+            [hi, lo] => {
+                let opcode = u16::from_be_bytes([hi, lo]);
+                let instruction = decode(opcode);
+                let target = match instruction {
+                    Instruction::Jp(nnn)
+                    | Instruction::Call(nnn)
+                    | Instruction::LdI(nnn)
+                    | Instruction::JpV0(nnn) => symbols.get(nnn),
+                    _ => None,
+                };
+                match target {
+                    Some(name) => format!("{addr:03X}  {opcode:04X}  {instruction}  ; {name}\n"),
+                    None => format!("{}\n", trace_line(addr as u16, opcode)),
+                }
+            }
+            [byte] => format!("{addr:03X}  {byte:02X}    DB {byte:#04X}\n"),
+            _ => unreachable!(),
+        };
+        listing.push_str(&line);
+    }
+    listing
+}
+
+// One listing line, e.g. "200  00E0  CLS", without symbols or a newline.
+pub fn trace_line(pc: u16, opcode: u16) -> String {
+    format!("{pc:03X}  {opcode:04X}  {}", decode(opcode))
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Instruction::Cls => write!(f, "CLS"),
+            Instruction::Ret => write!(f, "RET"),
+            Instruction::Exit => write!(f, "EXIT"),
+            Instruction::Sys(nnn) => write!(f, "SYS {nnn:#05X}"),
+            Instruction::Jp(nnn) => write!(f, "JP {nnn:#05X}"),
+            Instruction::Call(nnn) => write!(f, "CALL {nnn:#05X}"),
+            Instruction::SeByte { x, kk } => write!(f, "SE V{x:X}, {kk:#04X}"),
+            Instruction::SneByte { x, kk } => write!(f, "SNE V{x:X}, {kk:#04X}"),
+            Instruction::SeReg { x, y } => write!(f, "SE V{x:X}, V{y:X}"),
+            Instruction::LdByte { x, kk } => write!(f, "LD V{x:X}, {kk:#04X}"),
+            Instruction::AddByte { x, kk } => write!(f, "ADD V{x:X}, {kk:#04X}"),
+            Instruction::LdReg { x, y } => write!(f, "LD V{x:X}, V{y:X}"),
+            Instruction::Or { x, y } => write!(f, "OR V{x:X}, V{y:X}"),
+            Instruction::And { x, y } => write!(f, "AND V{x:X}, V{y:X}"),
+            Instruction::Xor { x, y } => write!(f, "XOR V{x:X}, V{y:X}"),
+            Instruction::AddReg { x, y } => write!(f, "ADD V{x:X}, V{y:X}"),
+            Instruction::Sub { x, y } => write!(f, "SUB V{x:X}, V{y:X}"),
+            Instruction::Shr { x, y } => write!(f, "SHR V{x:X}, V{y:X}"),
+            Instruction::Subn { x, y } => write!(f, "SUBN V{x:X}, V{y:X}"),
+            Instruction::Shl { x, y } => write!(f, "SHL V{x:X}, V{y:X}"),
+            Instruction::SneReg { x, y } => write!(f, "SNE V{x:X}, V{y:X}"),
+            Instruction::LdI(nnn) => write!(f, "LD I, {nnn:#05X}"),
+            Instruction::JpV0(nnn) => write!(f, "JP V0, {nnn:#05X}"),
+            Instruction::Rnd { x, kk } => write!(f, "RND V{x:X}, {kk:#04X}"),
+            Instruction::Drw { x, y, n } => write!(f, "DRW V{x:X}, V{y:X}, {n}"),
+            Instruction::Skp { x } => write!(f, "SKP V{x:X}"),
+            Instruction::Sknp { x } => write!(f, "SKNP V{x:X}"),
+            Instruction::LdVxDt { x } => write!(f, "LD V{x:X}, DT"),
+            Instruction::LdVxK { x } => write!(f, "LD V{x:X}, K"),
+            Instruction::LdDtVx { x } => write!(f, "LD DT, V{x:X}"),
+            Instruction::LdStVx { x } => write!(f, "LD ST, V{x:X}"),
+            Instruction::AddIVx { x } => write!(f, "ADD I, V{x:X}"),
+            Instruction::LdFVx { x } => write!(f, "LD F, V{x:X}"),
+            Instruction::LdBVx { x } => write!(f, "LD B, V{x:X}"),
+            Instruction::LdIVx { x } => write!(f, "LD [I], V{x:X}"),
+            Instruction::LdVxI { x } => write!(f, "LD V{x:X}, [I]"),
+            Instruction::Unknown(opcode) => {
+                let [hi, lo] = opcode.to_be_bytes();
+                write!(f, "DB {hi:#04X}, {lo:#04X}")
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::KEY_COUNT;
+
+// Canonical mapping of the QWERTY block 1234/QWER/ASDF/ZXCV onto the
+// COSMAC VIP hex keypad:
+//   1 2 3 C
+//   4 5 6 D
+//   7 8 9 E
+//   A 0 B F
+#[rustfmt::skip]
+pub const KEYMAP: [(char, u8); KEY_COUNT] = [
+    ('1', 0x1), ('2', 0x2), ('3', 0x3), ('4', 0xC),
+    ('Q', 0x4), ('W', 0x5), ('E', 0x6), ('R', 0xD),
+    ('A', 0x7), ('S', 0x8), ('D', 0x9), ('F', 0xE),
+    ('Z', 0xA), ('X', 0x0), ('C', 0xB), ('V', 0xF),
+];
+
+// Each key types its own hex digit: 0-9 on the number row, A-F on the
+// letter keys.
+#[rustfmt::skip]
+const NUMBER_ROW_KEYMAP: [(char, u8); KEY_COUNT] = [
+    ('1', 0x1), ('2', 0x2), ('3', 0x3), ('4', 0x4),
+    ('5', 0x5), ('6', 0x6), ('7', 0x7), ('8', 0x8),
+    ('9', 0x9), ('0', 0x0), ('A', 0xA), ('B', 0xB),
+    ('C', 0xC), ('D', 0xD), ('E', 0xE), ('F', 0xF),
+];
+
+// The same physical block as `KEYMAP` on an AZERTY keyboard, for
+// frontends whose key codes follow the printed letters.
+#[rustfmt::skip]
+const AZERTY_KEYMAP: [(char, u8); KEY_COUNT] = [
+    ('1', 0x1), ('2', 0x2), ('3', 0x3), ('4', 0xC),
+    ('A', 0x4), ('Z', 0x5), ('E', 0x6), ('R', 0xD),
+    ('Q', 0x7), ('S', 0x8), ('D', 0x9), ('F', 0xE),
+    ('W', 0xA), ('X', 0x0), ('C', 0xB), ('V', 0xF),
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum KeyLayout {
+    #[default]
+    Classic,
+    NumberRow,
+    Azerty,
+}
+
+impl KeyLayout {
+    pub fn parse(name: &str) -> Option<KeyLayout> {
+        match name {
+            "classic" => Some(KeyLayout::Classic),
+            "number-row" => Some(KeyLayout::NumberRow),
+            "azerty" => Some(KeyLayout::Azerty),
+            _ => None,
+        }
+    }
+
+    pub fn keymap(self) -> &'static [(char, u8); KEY_COUNT] {
+        match self {
+            KeyLayout::Classic => &KEYMAP,
+            KeyLayout::NumberRow => &NUMBER_ROW_KEYMAP,
+            KeyLayout::Azerty => &AZERTY_KEYMAP,
+        }
+    }
+
+    pub fn keypad_key(self, c: char) -> Option<u8> {
+        let c = c.to_ascii_uppercase();
+        self.keymap()
+            .iter()
+            .find(|(k, _)| *k == c)
+            .map(|(_, key)| *key)
+    }
+}
+
+pub fn keypad_key(c: char) -> Option<u8> {
+    KeyLayout::Classic.keypad_key(c)
+}
@@ -1,11 +1,44 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::fmt;
 use std::fs::File;
+use std::hash::{Hash, Hasher};
 use std::io::Read;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
-use rand::distributions::{Distribution, Uniform};
-use rand::rngs::ThreadRng;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
-const SPRITES: &'static [u8] = &[
+mod analysis;
+#[cfg(feature = "asm")]
+pub mod asm;
+mod audio;
+#[cfg(feature = "test-coverage")]
+pub mod coverage;
+mod display;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod instruction;
+mod keymap;
+mod quirks;
+mod rng;
+mod symbols;
+mod variant;
+
+pub use analysis::{analyze, RomAnalysis};
+pub use audio::Waveform;
+pub use instruction::{decode, disassemble, disassemble_with_symbols, trace_line, Instruction};
+pub use keymap::{keypad_key, KeyLayout, KEYMAP};
+pub use quirks::{rom_hash, Quirks};
+pub use rng::Chip8Rng;
+pub use symbols::SymbolMap;
+pub use variant::{detect_variant, Variant};
+
+use display::{Framebuffer, Pixels};
+
+const SPRITES: &[u8] = &[
     /*0*/ 0xF0, 0x90, 0x90, 0x90, 0xF0, /*1*/ 0x20, 0x60, 0x20, 0x20, 0x70,
     /*2*/ 0xF0, 0x10, 0xF0, 0x80, 0xF0, /*3*/ 0xF0, 0x10, 0xF0, 0x10, 0xF0,
     /*4*/ 0x90, 0x90, 0xF0, 0x10, 0x10, /*5*/ 0xF0, 0x80, 0xF0, 0x10, 0xF0,
@@ -23,71 +56,734 @@ pub const DISPLAY_WIDTH: usize = 64;
 pub const DISPLAY_HEIGHT: usize = 32;
 pub const DISPLAY_SIZE: usize = DISPLAY_WIDTH * DISPLAY_HEIGHT;
 const KEY_COUNT: usize = 16;
+const LOAD_BASE: u16 = 0x200;
+// Built from roms/splash.asm.
+const SPLASH_ROM: &[u8] = include_bytes!("../roms/splash.ch8");
+
+const TIMER_FREQ: u32 = 60;
+pub const DEFAULT_CLOCK_HZ: u32 = 800;
 
 #[allow(non_snake_case)]
+#[derive(Clone)]
 pub struct Chip8 {
     memory: [u8; MEMORY_SIZE],
     V: [u8; V_COUNT],
     stack: [u16; STACK_SIZE],
-    pub display: [bool; DISPLAY_WIDTH * DISPLAY_HEIGHT],
-    pub keys: [bool; KEY_COUNT],
+    display: Framebuffer,
+    keys: [bool; KEY_COUNT],
+    prev_keys: [bool; KEY_COUNT],
+    // Sequence number of each key's latest press, for Fx0A.
+    press_order: [u64; KEY_COUNT],
+    presses: u64,
     I: u16,
     pc: u16,
     sp: u8,
     DT: u8,
     ST: u8,
-    rng: ThreadRng,
-    rand_dist: Uniform<u8>,
-    tmp: bool,
+    rng: Box<dyn Chip8Rng>,
+    clock_hz: u32,
+    opcode_stats: OpcodeStats,
+    quirks: Quirks,
+    vblank: bool,
+    halted: bool,
+    waiting_for_key: bool,
+    frame: u64,
+    recording: bool,
+    input_trace: Vec<InputEvent>,
+    // The machine as it was when recording started, for `rewind_to_frame`.
+    origin: Option<Box<Chip8>>,
+    pending_input: VecDeque<InputEvent>,
+    timer_elapsed: Duration,
+    new_frame: bool,
+    exited: bool,
+    wait_for_key: Option<KeyCallback>,
+    on_cls: Option<ClsCallback>,
+    on_step: Option<StepCallback>,
+    draw_mode: DrawMode,
+    memory_access: MemoryAccess,
+    rom_endianness: Endianness,
+    waveform: Waveform,
+    // Position within the current beep period, in [0, 1).
+    audio_phase: f32,
+    symbols: SymbolMap,
+    log_collisions: bool,
+    collisions: Vec<(usize, usize)>,
+    // Highest address an instruction has been fetched from.
+    max_pc: u16,
+    log_self_modifying: bool,
+    self_modifying_writes: Vec<u16>,
 }
 
+// Supplies the key for Fx0A, or None to keep waiting.
+pub type KeyCallback = Arc<dyn Fn() -> Option<u8> + Send + Sync>;
+
+// Receives the outgoing display just before 00E0 clears it.
+pub type ClsCallback = Arc<dyn Fn(&[bool; DISPLAY_SIZE]) + Send + Sync>;
+
+// Receives (pc, opcode) for every instruction before it executes.
+pub type StepCallback = Arc<dyn Fn(u16, u16) + Send + Sync>;
+
+// A key transition as (frame number, key, pressed).
+pub type InputEvent = (u64, u8, bool);
+
 impl Chip8 {
     pub fn new() -> Chip8 {
+        Self::with_rng(Box::new(StdRng::from_entropy()))
+    }
+
+    pub fn with_seed(seed: u64) -> Chip8 {
+        Self::with_rng(Box::new(StdRng::seed_from_u64(seed)))
+    }
+
+    pub fn with_rng(rng: Box<dyn Chip8Rng>) -> Chip8 {
         let mut memory = [0; MEMORY_SIZE];
-        memory[..SPRITES.len()].clone_from_slice(&SPRITES);
+        memory[..SPRITES.len()].clone_from_slice(SPRITES);
 
         Chip8 {
             memory,
             V: [0; V_COUNT],
             stack: [0; STACK_SIZE],
-            display: [false; DISPLAY_WIDTH * DISPLAY_HEIGHT],
+            display: Framebuffer::new(),
             keys: [false; KEY_COUNT],
+            prev_keys: [false; KEY_COUNT],
+            press_order: [0; KEY_COUNT],
+            presses: 0,
             I: 0,
             pc: 0,
             sp: 0,
             DT: 0,
             ST: 0,
-            rng: rand::thread_rng(),
-            rand_dist: Uniform::from(0..0xFF),
-            tmp: false,
+            rng,
+            clock_hz: DEFAULT_CLOCK_HZ,
+            opcode_stats: OpcodeStats::default(),
+            quirks: Quirks::default(),
+            vblank: false,
+            halted: false,
+            waiting_for_key: false,
+            frame: 0,
+            recording: false,
+            input_trace: Vec::new(),
+            origin: None,
+            pending_input: VecDeque::new(),
+            timer_elapsed: Duration::ZERO,
+            new_frame: false,
+            exited: false,
+            wait_for_key: None,
+            on_cls: None,
+            on_step: None,
+            draw_mode: DrawMode::Xor,
+            memory_access: MemoryAccess::Strict,
+            rom_endianness: Endianness::Big,
+            waveform: Waveform::Square,
+            audio_phase: 0.0,
+            symbols: SymbolMap::default(),
+            log_collisions: false,
+            collisions: Vec::new(),
+            max_pc: 0,
+            log_self_modifying: false,
+            self_modifying_writes: Vec::new(),
+        }
+    }
+
+    // Installs a full memory dump verbatim, font area included, and resumes
+    // execution at `pc`. Everything else starts as in `new`: registers and
+    // timers cleared, `Quirks::default()` and an RNG seeded from entropy. Use
+    // `set_quirks` and `set_rng` to match the machine the dump came from.
+    pub fn from_memory_image(image: &[u8; MEMORY_SIZE], pc: u16) -> Chip8 {
+        let mut chip8 = Chip8::new();
+        chip8.memory = *image;
+        chip8.pc = pc;
+        chip8
+    }
+
+    pub fn set_rng(&mut self, rng: Box<dyn Chip8Rng>) {
+        self.rng = rng;
+    }
+
+    pub fn clock_hz(&self) -> u32 {
+        self.clock_hz
+    }
+
+    pub fn set_clock_hz(&mut self, clock_hz: u32) {
+        self.clock_hz = clock_hz;
+    }
+
+    pub fn cycles_per_frame(&self) -> usize {
+        (self.clock_hz / TIMER_FREQ) as usize
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    pub fn registers(&self) -> &[u8; V_COUNT] {
+        &self.V
+    }
+
+    pub fn pc(&self) -> u16 {
+        self.pc
+    }
+
+    // The instruction the next `cycle` will execute, without running it.
+    // None when pc is on the last byte of memory, where `cycle` fails.
+    pub fn current_instruction(&self) -> Option<Instruction> {
+        let high = self.peek(self.pc)?;
+        let low = self.peek(self.pc.checked_add(1)?)?;
+        Some(decode(u16::from_be_bytes([high, low])))
+    }
+
+    // Moves execution to `addr`, e.g. to skip an intro. Halted state is left
+    // alone; call `resume` as well to run from a self-jump.
+    pub fn set_pc(&mut self, addr: u16) -> Result<()> {
+        if addr as usize >= MEMORY_SIZE {
+            return Err(Error::AddressOutOfBounds(addr));
+        }
+        self.pc = addr;
+        Ok(())
+    }
+
+    // The flag register: carry, borrow, shifted-out bit or Dxyn collision.
+    pub fn vf(&self) -> u8 {
+        self.V[0xF]
+    }
+
+    pub fn delay_timer(&self) -> u8 {
+        self.DT
+    }
+
+    pub fn set_delay_timer(&mut self, value: u8) {
+        self.DT = value;
+    }
+
+    pub fn sound_timer(&self) -> u8 {
+        self.ST
+    }
+
+    pub fn set_sound_timer(&mut self, value: u8) {
+        self.ST = value;
+    }
+
+    pub fn is_beeping(&self) -> bool {
+        self.ST > 0
+    }
+
+    pub fn memory_access(&self) -> MemoryAccess {
+        self.memory_access
+    }
+
+    pub fn set_memory_access(&mut self, memory_access: MemoryAccess) {
+        self.memory_access = memory_access;
+    }
+
+    pub fn rom_endianness(&self) -> Endianness {
+        self.rom_endianness
+    }
+
+    // Applies to every later load.
+    pub fn set_rom_endianness(&mut self, rom_endianness: Endianness) {
+        self.rom_endianness = rom_endianness;
+    }
+
+    pub fn waveform(&self) -> Waveform {
+        self.waveform
+    }
+
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        self.waveform = waveform;
+    }
+
+    // Timer ticks left until the sound timer reaches zero and the beep stops.
+    pub fn sound_frames_remaining(&self) -> u8 {
+        self.ST
+    }
+
+    // Fills `out` with mono samples in [-1, 1]: the beep for as long as
+    // `sound_frames_remaining` lasts at 60Hz, silence after that. The phase
+    // carries over between calls so consecutive buffers join without clicks.
+    pub fn audio_samples(&mut self, out: &mut [f32], sample_rate: u32) {
+        let sample_rate = sample_rate.max(1);
+        let step = audio::BEEP_FREQ / sample_rate as f32;
+        let beep_len =
+            self.sound_frames_remaining() as usize * sample_rate as usize / TIMER_FREQ as usize;
+        for (i, sample) in out.iter_mut().enumerate() {
+            if i < beep_len {
+                *sample = self.waveform.sample(self.audio_phase);
+                self.audio_phase = (self.audio_phase + step).fract();
+            } else {
+                *sample = 0.0;
+            }
+        }
+    }
+
+    pub fn draw_mode(&self) -> DrawMode {
+        self.draw_mode
+    }
+
+    pub fn set_draw_mode(&mut self, draw_mode: DrawMode) {
+        self.draw_mode = draw_mode;
+    }
+
+    // Set by `halt` or once the program jumps to its own address, the idiom
+    // most ROMs use to stop. While halted `cycle` does nothing.
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn halt(&mut self) {
+        self.halted = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.halted = false;
+    }
+
+    // Set while Fx0A has found no key; until then each `cycle` just runs it
+    // again, so frontends may stop cycling. A new key press clears it. With a
+    // `set_wait_for_key` callback the key does not arrive that way, so keep
+    // cycling instead.
+    pub fn is_waiting_for_key(&self) -> bool {
+        self.waiting_for_key
+    }
+
+    // Set by the SUPER-CHIP 00FD instruction; no further cycles run.
+    pub fn has_exited(&self) -> bool {
+        self.exited
+    }
+
+    // When set, Fx0A asks the callback for a key instead of polling `keys`.
+    pub fn set_wait_for_key(&mut self, callback: Option<KeyCallback>) {
+        self.wait_for_key = callback;
+    }
+
+    pub fn set_on_cls(&mut self, callback: Option<ClsCallback>) {
+        self.on_cls = callback;
+    }
+
+    pub fn set_on_step(&mut self, callback: Option<StepCallback>) {
+        self.on_step = callback;
+    }
+
+    // Number of timer ticks since power-on; input events are stamped with it.
+    pub fn frame_count(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn press_key(&mut self, key: u8) {
+        self.set_key(key, true);
+    }
+
+    pub fn release_key(&mut self, key: u8) {
+        self.set_key(key, false);
+    }
+
+    // Frontends call this when they lose focus, since the matching release
+    // events will never arrive. A pending Fx0A wait is dropped too; the
+    // instruction starts waiting afresh when it next runs.
+    pub fn release_all_keys(&mut self) {
+        for key in 0..KEY_COUNT as u8 {
+            if self.keys[key as usize] {
+                self.release_key(key);
+            }
         }
+        self.waiting_for_key = false;
+    }
+
+    pub fn is_key_pressed(&self, key: u8) -> bool {
+        self.keys.get(key as usize).copied().unwrap_or(false)
+    }
+
+    pub fn pressed_keys(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..KEY_COUNT as u8).filter(|&key| self.keys[key as usize])
+    }
+
+    // Edges are relative to the key state at the last latch, which `timer`
+    // takes at every tick.
+    pub fn just_pressed(&self, key: u8) -> bool {
+        key < KEY_COUNT as u8 && self.keys[key as usize] && !self.prev_keys[key as usize]
+    }
+
+    pub fn just_released(&self, key: u8) -> bool {
+        key < KEY_COUNT as u8 && !self.keys[key as usize] && self.prev_keys[key as usize]
+    }
+
+    pub fn latch_input(&mut self) {
+        self.prev_keys = self.keys;
+    }
+
+    fn set_key(&mut self, key: u8, pressed: bool) {
+        let key = key & 0xF;
+        if pressed && !self.keys[key as usize] {
+            self.presses += 1;
+            self.press_order[key as usize] = self.presses;
+            self.waiting_for_key = false;
+        }
+        self.keys[key as usize] = pressed;
+        if self.recording {
+            self.input_trace.push((self.frame, key, pressed));
+        }
+    }
+
+    // Starting a recording discards any previous trace and snapshots the
+    // machine; stopping it drops the snapshot.
+    pub fn set_recording(&mut self, recording: bool) {
+        if recording && !self.recording {
+            self.input_trace.clear();
+            self.origin = None;
+            self.origin = Some(Box::new(self.clone()));
+        } else if !recording {
+            self.origin = None;
+        }
+        self.recording = recording;
+    }
+
+    // Rebuilds the machine at `frame` by replaying the input trace from the
+    // recording snapshot. The result is exact as long as the machine was only
+    // advanced with `step_frame` while recording. Events after `frame` are
+    // discarded, so recording carries on from the rewound state.
+    pub fn rewind_to_frame(&mut self, frame: u64) -> Result<()> {
+        let mut rewound = match &self.origin {
+            Some(origin) if (origin.frame..=self.frame).contains(&frame) => (**origin).clone(),
+            _ => return Err(Error::FrameNotRecorded(frame)),
+        };
+        rewound.recording = false;
+        rewound.on_cls = None;
+        rewound.on_step = None;
+        rewound.wait_for_key = self.wait_for_key.clone();
+        rewound.replay_input(&self.input_trace);
+        while rewound.frame < frame {
+            rewound.step_frame()?;
+        }
+
+        rewound.pending_input.clear();
+        rewound.recording = true;
+        rewound.origin = self.origin.take();
+        rewound.input_trace = std::mem::take(&mut self.input_trace);
+        rewound
+            .input_trace
+            .retain(|&(event_frame, _, _)| event_frame <= frame);
+        rewound.on_cls = self.on_cls.take();
+        rewound.on_step = self.on_step.take();
+        *self = rewound;
+        Ok(())
+    }
+
+    pub fn input_trace(&self) -> &[InputEvent] {
+        &self.input_trace
+    }
+
+    // Queues a recorded trace; each event is applied once the frame counter
+    // reaches its frame number. Events for past frames are applied right away.
+    pub fn replay_input(&mut self, trace: &[InputEvent]) {
+        self.pending_input.extend(trace.iter().copied());
+        self.apply_pending_input();
+    }
+
+    fn apply_pending_input(&mut self) {
+        while let Some(&(frame, key, pressed)) = self.pending_input.front() {
+            if frame > self.frame {
+                break;
+            }
+            self.pending_input.pop_front();
+            self.set_key(key, pressed);
+        }
+    }
+
+    // When enabled, each Dxyn records the pixels it turned off.
+    pub fn set_collision_log(&mut self, enabled: bool) {
+        self.log_collisions = enabled;
+        self.collisions.clear();
+    }
+
+    pub fn last_collisions(&self) -> &[(usize, usize)] {
+        &self.collisions
+    }
+
+    // When enabled, Fx33 and Fx55 record every address they write between
+    // 0x200 and the last byte of the highest instruction executed so far.
+    // The log grows until it is disabled, which clears it.
+    pub fn set_self_modifying_log(&mut self, enabled: bool) {
+        self.log_self_modifying = enabled;
+        self.self_modifying_writes.clear();
+    }
+
+    pub fn self_modifying_writes(&self) -> &[u16] {
+        &self.self_modifying_writes
+    }
+
+    pub fn set_symbols(&mut self, symbols: SymbolMap) {
+        self.symbols = symbols;
+    }
+
+    pub fn symbols(&self) -> &SymbolMap {
+        &self.symbols
+    }
+
+    pub fn symbol_for(&self, addr: u16) -> Option<&str> {
+        self.symbols.get(addr)
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            memory: self.memory,
+            V: self.V,
+            stack: self.stack,
+            display: self.display.clone(),
+            I: self.I,
+            pc: self.pc,
+            sp: self.sp,
+            DT: self.DT,
+            ST: self.ST,
+            halted: self.halted,
+            exited: self.exited,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.memory = snapshot.memory;
+        self.V = snapshot.V;
+        self.stack = snapshot.stack;
+        self.display = snapshot.display.clone();
+        self.I = snapshot.I;
+        self.pc = snapshot.pc;
+        self.sp = snapshot.sp;
+        self.DT = snapshot.DT;
+        self.ST = snapshot.ST;
+        self.halted = snapshot.halted;
+        self.exited = snapshot.exited;
+        self.waiting_for_key = false;
+    }
+
+    // A copy of the whole address space, suitable for `from_memory_image`.
+    pub fn export_memory(&self) -> [u8; MEMORY_SIZE] {
+        self.memory
+    }
+
+    pub fn peek(&self, addr: u16) -> Option<u8> {
+        self.memory.get(addr as usize).copied()
+    }
+
+    // Copies data into program memory at `offset` without touching pc, e.g. to
+    // patch in an overlay at runtime.
+    pub fn write_rom_region(&mut self, data: &[u8], offset: u16) -> Result<()> {
+        let begin = offset as usize;
+        if begin < 0x200 {
+            return Err(Error::AddressOutOfBounds(offset));
+        }
+        if begin >= MEMORY_SIZE || data.len() > MEMORY_SIZE - begin {
+            return Err(Error::ROMDoesNotFit(data.len() as u64, offset));
+        }
+        self.memory[begin..begin + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    pub fn memory_slice(&self, start: u16, len: u16) -> Option<&[u8]> {
+        let start = start as usize;
+        self.memory.get(start..start + len as usize)
+    }
+
+    pub fn memory_regions(&self) -> Vec<MemoryRegion> {
+        let font_end = SPRITES.len() as u16 - 1;
+        vec![
+            MemoryRegion {
+                name: "font",
+                start: 0x000,
+                end: font_end,
+            },
+            MemoryRegion {
+                name: "reserved",
+                start: font_end + 1,
+                end: 0x1FF,
+            },
+            MemoryRegion {
+                name: "program",
+                start: 0x200,
+                end: (MEMORY_SIZE - 1) as u16,
+            },
+        ]
+    }
+
+    pub fn opcode_stats(&self) -> OpcodeStats {
+        self.opcode_stats
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, on: bool) -> Result<()> {
+        if x >= DISPLAY_WIDTH || y >= DISPLAY_HEIGHT {
+            return Err(Error::PixelOutOfBounds(x, y));
+        }
+        self.display.set(x + DISPLAY_WIDTH * y, on);
+        Ok(())
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<bool> {
+        if x >= DISPLAY_WIDTH || y >= DISPLAY_HEIGHT {
+            return None;
+        }
+        Some(self.display.get(x + DISPLAY_WIDTH * y))
+    }
+
+    // A copy of every pixel in row-major order, whichever way the
+    // framebuffer is stored.
+    pub fn display(&self) -> [bool; DISPLAY_SIZE] {
+        self.display.to_array()
+    }
+
+    // Panics unless `out` holds exactly DISPLAY_SIZE pixels.
+    pub fn copy_display_into(&self, out: &mut [bool]) {
+        assert_eq!(out.len(), DISPLAY_SIZE, "display buffer has the wrong size");
+        for (pixel, on) in out.iter_mut().zip(self.display.iter()) {
+            *pixel = on;
+        }
+    }
+
+    // Draws `sprite` the way Dxyn does, one byte per row, honoring the draw
+    // mode and clipping quirk, and returns whether a lit pixel was turned off.
+    // VF is left alone.
+    pub fn draw_sprite(&mut self, x: u8, y: u8, sprite: &[u8]) -> bool {
+        self.blit(x, y, sprite).0
+    }
+
+    // Returns (collision, whether any pixel was drawn).
+    fn blit(&mut self, x: u8, y: u8, sprite: &[u8]) -> (bool, bool) {
+        let x = x as usize % DISPLAY_WIDTH;
+        let y = y as usize % DISPLAY_HEIGHT;
+        let mut collision = false;
+        let mut changed = false;
+        self.collisions.clear();
+
+        for (i, byte) in sprite.iter().enumerate() {
+            for j in 0..8 {
+                let (px, py) = (x + j, y + i);
+                if self.quirks.clipping && (px >= DISPLAY_WIDTH || py >= DISPLAY_HEIGHT) {
+                    continue;
+                }
+                let bit = ((byte >> (7 - j)) & 1) != 0;
+                changed |= bit;
+                let index = px % DISPLAY_WIDTH + DISPLAY_WIDTH * (py % DISPLAY_HEIGHT);
+                match self.draw_mode {
+                    DrawMode::Xor => {
+                        let on = self.display.get(index);
+                        if on && bit {
+                            collision = true;
+                            if self.log_collisions {
+                                self.collisions
+                                    .push((index % DISPLAY_WIDTH, index / DISPLAY_WIDTH));
+                            }
+                        }
+                        self.display.set(index, on ^ bit);
+                    }
+                    DrawMode::Or => {
+                        if bit {
+                            self.display.set(index, true);
+                        }
+                    }
+                }
+            }
+        }
+        (collision, changed)
+    }
+
+    // Every pixel in row-major order as (x, y, on).
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize, bool)> + '_ {
+        self.display
+            .iter()
+            .enumerate()
+            .map(|(i, on)| (i % DISPLAY_WIDTH, i / DISPLAY_WIDTH, on))
+    }
+
+    // Everything from the load base to the end of memory: 3584 bytes on the
+    // classic 4K machine.
+    pub const fn max_rom_size() -> u64 {
+        (MEMORY_SIZE - LOAD_BASE as usize) as u64
     }
 
     pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
-        let mut file = File::open(path)?;
+        let file = File::open(path)?;
         let romsize = file.metadata()?.len();
-        if romsize > (0xFFF - 0x200) {
-            return Err(Error::ROMIsTooBig(romsize));
+        let limit = Self::max_rom_size();
+        if romsize > limit {
+            return Err(Error::ROMIsTooBig(romsize, limit));
+        }
+        // Only cast once the size is known to be at most `limit`, so a
+        // length past `u32::MAX` cannot wrap on 32-bit targets.
+        let mut rom = Vec::with_capacity(romsize as usize);
+        // The file may have grown since its size was read.
+        file.take(limit + 1).read_to_end(&mut rom)?;
+        self.load_from_bytes(&rom)
+    }
+
+    pub fn load_from_bytes(&mut self, rom: &[u8]) -> Result<()> {
+        let romsize = u64::try_from(rom.len()).unwrap_or(u64::MAX);
+        let limit = Self::max_rom_size();
+        if romsize > limit {
+            return Err(Error::ROMIsTooBig(romsize, limit));
+        }
+        self.load_at(rom, LOAD_BASE)
+    }
+
+    // Loads a built-in program that shows "C8", for frontends started
+    // without a ROM.
+    pub fn load_default(&mut self) {
+        // The splash is stored big-endian whatever the ROM setting.
+        let rom_endianness = std::mem::take(&mut self.rom_endianness);
+        self.load_from_bytes(SPLASH_ROM)
+            .expect("The splash ROM fits in memory.");
+        self.rom_endianness = rom_endianness;
+    }
+
+    // ETI-660 programs expect to start at 0x600 rather than the usual 0x200.
+    pub fn load_at(&mut self, rom: &[u8], start: u16) -> Result<()> {
+        let begin = start as usize;
+        if begin >= MEMORY_SIZE || rom.len() > MEMORY_SIZE - begin {
+            return Err(Error::ROMDoesNotFit(rom.len() as u64, start));
+        }
+        let program = &mut self.memory[begin..begin + rom.len()];
+        program.copy_from_slice(rom);
+        if self.rom_endianness == Endianness::Little {
+            // A trailing odd byte has no partner and stays as is.
+            for word in program.chunks_exact_mut(2) {
+                word.swap(0, 1);
+            }
         }
-        file.read_exact(&mut self.memory[0x200..0x200 + romsize as usize])?;
-        self.pc = 0x200;
+        self.pc = start;
+        self.halted = false;
+        self.exited = false;
+        self.waiting_for_key = false;
+        // The previous program's code region says nothing about this one.
+        self.max_pc = 0;
+        self.self_modifying_writes.clear();
         Ok(())
     }
 
-    pub fn cycle(&mut self) {
-        if self.tmp {
-            return;
+    pub fn cycle(&mut self) -> Result<StepResult> {
+        if self.halted || self.exited {
+            return Ok(StepResult::default());
+        }
+        if self.pc as usize + 1 >= MEMORY_SIZE {
+            return Err(Error::AddressOutOfBounds(self.pc));
         }
         let opcode: u16 = ((self.memory[self.pc as usize] as u16) << 8)
             | self.memory[(self.pc + 1) as usize] as u16;
+        self.max_pc = self.max_pc.max(self.pc);
+        if let Some(on_step) = &self.on_step {
+            on_step(self.pc, opcode);
+        }
         self.pc += 2;
+        let next_pc = self.pc;
+        let sound_timer = self.ST;
+        let mut display_changed = false;
+        // Cleared when the instruction has to run again, so a wait spanning
+        // many cycles is counted once.
+        let mut retired = true;
 
         let o = (opcode & 0xF000) >> 12;
-        let nnn = opcode & 0x0FFF;
         let n = opcode & 0x000F;
-        let x = (opcode & 0x0F00) >> 8;
-        let y = (opcode & 0x00F0) >> 4;
         let kk = (opcode & 0x00FF) as u8;
+        #[cfg(feature = "test-coverage")]
+        coverage::record(o);
 
         macro_rules! V {
             ($offset:expr) => {
@@ -95,200 +791,324 @@ impl Chip8 {
             };
         }
 
-        macro_rules! Vx {
-            () => {
-                self.V[x as usize]
-            };
-        }
-
-        macro_rules! Vy {
-            () => {
-                self.V[y as usize]
-            };
-        }
-
-        //println!("opcode: {:#02X}", opcode);
-
-        match (o, kk, n) {
-            // 0x00E0 - CLS
-            (0, 0xE0, _) => self.display.fill(false),
-            // 0x00EE - RET
-            (0, 0xEE, _) => {
+        match decode(opcode) {
+            Instruction::Cls => {
+                if let Some(on_cls) = &self.on_cls {
+                    on_cls(&self.display.to_array());
+                }
+                self.display.clear();
+                display_changed = true;
+            }
+            Instruction::Ret => {
+                if self.sp == 0 {
+                    return Err(Error::StackUnderflow);
+                }
                 self.pc = self.stack[self.sp as usize];
                 self.sp -= 1;
             }
-            // 0x1nnn - JP addr
-            (1, _, _) => self.pc = nnn,
-            // 0x2nnn - CALL addr
-            (2, _, _) => {
+            Instruction::Exit => self.exited = true,
+            // Machine code routines can't run here, so SYS is ignored. 0000 is
+            // what an empty ROM or running off the end of one executes, so it
+            // halts in place instead.
+            Instruction::Sys(nnn) => {
+                if nnn == 0 {
+                    self.pc -= 2;
+                    self.halted = true;
+                }
+            }
+            Instruction::Jp(nnn) => {
+                if nnn == next_pc - 2 {
+                    self.halted = true;
+                }
+                self.pc = nnn;
+            }
+            Instruction::Call(nnn) => {
+                if self.sp as usize + 1 >= STACK_SIZE {
+                    return Err(Error::StackOverflow);
+                }
                 self.sp += 1;
                 self.stack[self.sp as usize] = self.pc;
                 self.pc = nnn;
             }
-            // 3xkk - SE Vx, byte
-            (3, _, _) => {
-                if Vx!() == kk {
+            Instruction::SeByte { x, kk } => {
+                if V!(x) == kk {
                     self.pc += 2;
                 }
             }
-            // 4xkk - SNE Vx, byte
-            (4, _, _) => {
-                if Vx!() != kk {
+            Instruction::SneByte { x, kk } => {
+                if V!(x) != kk {
                     self.pc += 2;
                 }
             }
-            // 5xy0 - SE Vx, Vy
-            (5, _, 0) => {
-                if Vx!() == Vy!() {
+            Instruction::SeReg { x, y } => {
+                if V!(x) == V!(y) {
                     self.pc += 2;
                 }
             }
-            // 6xkk - LD Vx, byte
-            (6, _, _) => {
-                Vx!() = kk;
-            }
-            // 7xkk - ADD Vx, byte
-            (7, _, _) => Vx!() += kk,
-            // 8xy0 - LD Vx, Vy
-            (8, _, 0) => Vx!() = Vy!(),
-            // 8xy1 - OR Vx, Vy
-            (8, _, 1) => Vx!() |= Vy!(),
-            // 8xy2 - AND Vx, Vy
-            (8, _, 2) => Vx!() &= Vy!(),
-            // 8xy3 - XOR Vx, Vy
-            (8, _, 3) => Vx!() ^= Vy!(),
-            // 8xy4 - ADD Vx, Vy
-            (8, _, 4) => {
-                let sum = Vx!() as u16 + Vy!() as u16;
-                if sum > 0xFF {
-                    V!(0xF) = 1;
-                } else {
+            Instruction::LdByte { x, kk } => V!(x) = kk,
+            Instruction::AddByte { x, kk } => V!(x) = V!(x).wrapping_add(kk),
+            Instruction::LdReg { x, y } => V!(x) = V!(y),
+            Instruction::Or { x, y } => {
+                V!(x) |= V!(y);
+                if self.quirks.vf_reset {
                     V!(0xF) = 0;
                 }
-                Vx!() = (sum & 0xFF) as u8;
             }
-            // 8xy5 - SUB Vx, Vy
-            (8, _, 5) => {
-                if Vx!() >= Vy!() {
-                    V!(0xF) = 1;
-                } else {
+            Instruction::And { x, y } => {
+                V!(x) &= V!(y);
+                if self.quirks.vf_reset {
                     V!(0xF) = 0;
                 }
-                Vx!() -= Vy!()
-            }
-            // 8xy6 - SHR Vx {, Vy}
-            (8, _, 6) => {
-                V!(0xF) = Vx!() & 1;
-                Vx!() >>= 1;
             }
-            // 8xy7 - SUBN Vx, Vy
-            (8, _, 7) => {
-                if Vy!() >= Vx!() {
-                    V!(0xF) = 1;
-                } else {
+            Instruction::Xor { x, y } => {
+                V!(x) ^= V!(y);
+                if self.quirks.vf_reset {
                     V!(0xF) = 0;
                 }
-                Vx!() = Vy!() - Vx!();
             }
-            // 8xyE - SHL Vx {, Vy}
-            (8, _, 0xE) => {
-                V!(0xF) = Vx!() >> 7;
-                Vx!() <<= 1;
+            // The flag is written after the result, so VF holds the flag when
+            // it is also the destination.
+            Instruction::AddReg { x, y } => {
+                let (sum, carry) = V!(x).overflowing_add(V!(y));
+                V!(x) = sum;
+                V!(0xF) = carry as u8;
+            }
+            Instruction::Sub { x, y } => {
+                let (difference, borrow) = V!(x).overflowing_sub(V!(y));
+                V!(x) = difference;
+                V!(0xF) = !borrow as u8;
+            }
+            Instruction::Shr { x, y } => {
+                let value = if self.quirks.shifting { V!(x) } else { V!(y) };
+                V!(x) = value >> 1;
+                V!(0xF) = value & 1;
             }
-            // 9xy0 - SNE Vx, Vy
-            (9, _, 0) => {
-                if Vx!() != Vy!() {
+            Instruction::Subn { x, y } => {
+                let (difference, borrow) = V!(y).overflowing_sub(V!(x));
+                V!(x) = difference;
+                V!(0xF) = !borrow as u8;
+            }
+            Instruction::Shl { x, y } => {
+                let value = if self.quirks.shifting { V!(x) } else { V!(y) };
+                V!(x) = value << 1;
+                V!(0xF) = value >> 7;
+            }
+            Instruction::SneReg { x, y } => {
+                if V!(x) != V!(y) {
                     self.pc += 2;
                 }
             }
-            // Annn - LD I, addr
-            (0xA, _, _) => {
-                self.I = nnn;
-            }
-            // Bnnn - JP V0, addr
-            (0xB, _, _) => self.pc = nnn + V!(0) as u16,
-            // Cxkk - RND Vx, byte
-            (0xC, _, _) => {
-                let random = self.rand_dist.sample(&mut self.rng);
-                Vx!() = random & kk;
-            }
-            // Dxyn - DRW Vx, Vy, nibble
-            (0xD, _, _) => {
-                let x = Vx!() as u16;
-                let y = Vy!() as u16;
-                V!(0xF) = 0;
-
-                for i in 0..n {
-                    let byte = self.memory[self.I as usize + i as usize];
-                    for j in (0..8).rev() {
-                        let bit = ((byte >> j) & 1) != 0;
-                        let index = ((x + (7 - j)) % (DISPLAY_WIDTH as u16)
-                            + (DISPLAY_WIDTH as u16) * ((y + i) % (DISPLAY_HEIGHT as u16)))
-                            as usize;
-                        if self.display[index] && bit {
-                            V!(0xF) = 1;
-                        }
-                        self.display[index] ^= bit;
-                    }
+            // nnn is 12 bits, so Annn reaches all of the 4K address space
+            // and needs no masking.
+            Instruction::LdI(nnn) => self.I = nnn,
+            Instruction::JpV0(nnn) => {
+                let offset = if self.quirks.jumping {
+                    V!(nnn >> 8)
+                } else {
+                    V!(0)
+                };
+                // nnn + offset reaches up to 0x10FE; wrap it into the 12-bit
+                // address space instead of faulting on the next fetch.
+                self.pc = (nnn + offset as u16) % MEMORY_SIZE as u16;
+            }
+            Instruction::Rnd { x, kk } => {
+                let random = self.rng.next_byte();
+                V!(x) = random & kk;
+            }
+            // Held by the display-wait quirk, Dxyn runs again until the tick.
+            Instruction::Drw { .. } if self.quirks.display_wait && !self.vblank => {
+                self.pc -= 2;
+                retired = false;
+            }
+            Instruction::Drw { x, y, n } => {
+                self.vblank = false;
+
+                // Rows that would be read from past the end of memory are
+                // dropped, so a sprite near the top of memory draws partially
+                // and one with I past the end draws nothing.
+                let sprite = self.memory.get(self.I as usize..).unwrap_or(&[]);
+                let rows = (n as usize).min(sprite.len());
+                let mut buf = [0; 0xF];
+                buf[..rows].copy_from_slice(&sprite[..rows]);
+
+                let (collision, changed) = self.blit(V!(x), V!(y), &buf[..rows]);
+                display_changed = changed;
+                if self.draw_mode == DrawMode::Xor {
+                    V!(0xF) = collision as u8;
                 }
             }
-            // Ex9E - SKP Vx
-            (0xE, 0x9E, _) => {
-                if self.keys[Vx!() as usize] {
+            // Only the low nibble of Vx selects a key, as on the COSMAC VIP.
+            Instruction::Skp { x } => {
+                if self.keys[(V!(x) & 0xF) as usize] {
                     self.pc += 2;
                 }
             }
-            // ExA1 - SKNP Vx
-            (0xE, 0xA1, _) => {
-                if !self.keys[Vx!() as usize] {
+            Instruction::Sknp { x } => {
+                if !self.keys[(V!(x) & 0xF) as usize] {
                     self.pc += 2;
                 }
             }
-            // Fx07 - LD Vx, DT
-            (0xF, 0x07, _) => Vx!() = self.DT,
-            // Fx0A - LD Vx, K
-            (0xF, 0x0A, _) => {
+            Instruction::LdVxDt { x } => V!(x) = self.DT,
+            // With several keys down, the most recently pressed one wins.
+            Instruction::LdVxK { x } => {
                 self.pc -= 2;
-                for (i, key) in self.keys.iter().enumerate() {
-                    if *key {
-                        Vx!() = i as u8;
-                        self.pc += 2;
-                        break;
-                    }
+                let key = match &self.wait_for_key {
+                    Some(wait_for_key) => wait_for_key(),
+                    None => self
+                        .pressed_keys()
+                        .max_by_key(|&key| self.press_order[key as usize]),
+                };
+                self.waiting_for_key = key.is_none();
+                retired = !self.waiting_for_key;
+                if let Some(key) = key {
+                    V!(x) = key & 0xF;
+                    self.pc += 2;
+                }
+            }
+            Instruction::LdDtVx { x } => self.DT = V!(x),
+            Instruction::LdStVx { x } => self.ST = V!(x),
+            Instruction::AddIVx { x } => self.I = self.I.wrapping_add(V!(x) as u16),
+            Instruction::LdFVx { x } => self.I = V!(x) as u16 * 5,
+            // Each access resolves its last address first, so in strict mode
+            // nothing is written when it runs past the end of memory.
+            Instruction::LdBVx { x } => {
+                let value = V!(x);
+                self.i_address(2)?;
+                for (offset, digit) in [value / 100, (value / 10) % 10, value % 10]
+                    .into_iter()
+                    .enumerate()
+                {
+                    let addr = self.i_address(offset)?;
+                    self.store(addr, digit);
                 }
             }
-            // Fx15 - LD DT, Vx
-            (0xF, 0x15, _) => self.DT = Vx!(),
-            // Fx18 - LD ST, Vx
-            (0xF, 0x18, _) => self.ST = Vx!(),
-            // Fx1E - ADD I, Vx
-            (0xF, 0x1E, _) => self.I += Vx!() as u16,
-            // Fx29 - LD F, Vx
-            (0xF, 0x29, _) => self.I = Vx!() as u16 * 5,
-            // Fx33 - LD B, Vx
-            (0xF, 0x33, _) => {
-                self.memory[self.I as usize] = (Vx!() / 100) % 10;
-                self.memory[self.I as usize + 1] = (Vx!() / 10) % 10;
-                self.memory[self.I as usize + 2] = Vx!() % 10;
-            }
-            // Fx55 - LD [I], Vx
-            (0xF, 0x55, _) => {
+            Instruction::LdIVx { x } => {
+                self.i_address(x as usize)?;
                 for offset in 0..=x as usize {
-                    self.memory[self.I as usize + offset] = self.V[offset];
+                    let addr = self.i_address(offset)?;
+                    self.store(addr, self.V[offset]);
+                }
+                if self.quirks.memory {
+                    self.I = self.I.wrapping_add(x as u16 + 1);
                 }
             }
-            // Fx65 - LD Vx, [I]
-            (0xF, 0x65, _) => {
+            Instruction::LdVxI { x } => {
+                self.i_address(x as usize)?;
                 for offset in 0..=x as usize {
-                    V!(offset) = self.memory[self.I as usize + offset];
+                    let addr = self.i_address(offset)?;
+                    V!(offset) = self.memory[addr];
+                }
+                if self.quirks.memory {
+                    self.I = self.I.wrapping_add(x as u16 + 1);
                 }
             }
+            Instruction::Unknown(opcode) => return Err(Error::UnknownOpcode(opcode)),
+        }
+        if retired {
+            self.opcode_stats.record(o, kk, n);
+        }
+        self.check_invariants();
 
-            _ => unimplemented!("Unrecoginized opcode: {opcode:#X}"),
+        Ok(StepResult {
+            opcode,
+            display_changed,
+            sound_started: sound_timer == 0 && self.ST > 0,
+            pc_changed_nonlinearly: self.pc != next_pc,
+        })
+    }
+
+    fn store(&mut self, addr: usize, value: u8) {
+        if self.log_self_modifying && (0x200..=self.max_pc as usize + 1).contains(&addr) {
+            self.self_modifying_writes.push(addr as u16);
+        }
+        self.memory[addr] = value;
+    }
+
+    // I + offset as a memory index: an error past the end of memory in
+    // strict mode, wrapped around to the start in lenient mode.
+    fn i_address(&self, offset: usize) -> Result<usize> {
+        let addr = self.I as usize + offset;
+        match self.memory_access {
+            MemoryAccess::Strict if addr >= MEMORY_SIZE => Err(Error::AddressOutOfBounds(self.I)),
+            _ => Ok(addr % MEMORY_SIZE),
+        }
+    }
+
+    // State the interpreter itself guarantees whatever the ROM does. PC and I
+    // are left out: a ROM can legitimately move them past the end of memory.
+    // A fetch from there is AddressOutOfBounds, Fx33, Fx55 and Fx65 follow
+    // the memory access mode, and Dxyn drops the rows it cannot read. A call
+    // from the last word of memory pushes MEMORY_SIZE itself.
+    fn check_invariants(&self) {
+        debug_assert!(
+            (self.sp as usize) < STACK_SIZE,
+            "stack pointer {} is past the end of the stack",
+            self.sp
+        );
+        debug_assert!(
+            self.stack[1..=self.sp as usize]
+                .iter()
+                .all(|&addr| addr as usize <= MEMORY_SIZE),
+            "return address past the end of memory in {:03X?}",
+            &self.stack[1..=self.sp as usize]
+        );
+    }
+
+    // `cycle` for tooling: returns the instruction that was fetched. While
+    // halted or exited nothing is fetched and this returns Sys(0).
+    pub fn step(&mut self) -> Result<Instruction> {
+        self.cycle().map(|result| decode(result.opcode))
+    }
+
+    // Runs one frame's worth of cycles back to back, then ticks the timers once.
+    pub fn step_frame(&mut self) -> Result<()> {
+        for _ in 0..self.cycles_per_frame() {
+            if self.halted || self.exited {
+                break;
+            }
+            self.cycle()?;
+        }
+        self.timer();
+        Ok(())
+    }
+
+    // Like `step_frame`, but also stops early at a Dxyn held back by the
+    // display-wait quirk, which would otherwise spin until the tick. Returns
+    // how many instructions actually ran; the held-back Dxyn is not counted.
+    pub fn run_to_vblank(&mut self) -> Result<usize> {
+        let mut executed = 0;
+        while executed < self.cycles_per_frame() && !self.halted && !self.exited {
+            let pc = self.pc;
+            let result = self.cycle()?;
+            if self.pc == pc && matches!(decode(result.opcode), Instruction::Drw { .. }) {
+                break;
+            }
+            executed += 1;
+        }
+        self.timer();
+        Ok(executed)
+    }
+
+    // Runs up to `frames` frames without pacing, stopping early once the
+    // program halts or exits; `frame_count` then tells where it stopped.
+    pub fn fast_forward(&mut self, frames: usize) -> Result<()> {
+        for _ in 0..frames {
+            if self.halted || self.exited {
+                break;
+            }
+            self.step_frame()?;
         }
+        Ok(())
+    }
+
+    // Returns whether a timer tick happened since the last call.
+    pub fn take_new_frame(&mut self) -> bool {
+        std::mem::take(&mut self.new_frame)
     }
 
     pub fn timer(&mut self) {
+        self.vblank = true;
+        self.new_frame = true;
         if self.DT > 0 {
             self.DT -= 1;
         }
@@ -296,6 +1116,178 @@ impl Chip8 {
         if self.ST > 0 {
             self.ST -= 1;
         }
+
+        self.frame += 1;
+        self.latch_input();
+        self.apply_pending_input();
+    }
+
+    // Runs one timer tick per whole 1/60s in `elapsed`, carrying the
+    // remainder over to the next call. DT and ST only change on those whole
+    // ticks, so Fx07 never sees a partially elapsed period.
+    pub fn tick_timers(&mut self, elapsed: Duration) {
+        let period = Duration::from_secs(1) / TIMER_FREQ;
+        self.timer_elapsed += elapsed;
+        while self.timer_elapsed >= period {
+            self.timer_elapsed -= period;
+            self.timer();
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StepResult {
+    pub opcode: u16,
+    pub display_changed: bool,
+    pub sound_started: bool,
+    // Anything but moving on to the next instruction, including an Fx0A or
+    // Dxyn that is held to run again.
+    pub pc_changed_nonlinearly: bool,
+}
+
+// How Dxyn combines sprites with the display. `Or` is a debugging aid that
+// only ever sets pixels and leaves VF untouched.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DrawMode {
+    #[default]
+    Xor,
+    Or,
+}
+
+// Machine state saved by `Chip8::snapshot`. Keys, the RNG and frontend
+// settings are not part of it, so restoring only rolls back what the program
+// itself can see and change.
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    memory: [u8; MEMORY_SIZE],
+    V: [u8; V_COUNT],
+    stack: [u16; STACK_SIZE],
+    display: Framebuffer,
+    I: u16,
+    pc: u16,
+    sp: u8,
+    DT: u8,
+    ST: u8,
+    halted: bool,
+    exited: bool,
+}
+
+// What Fx33, Fx55 and Fx65 do when I points them past the end of memory.
+// Strict stops with AddressOutOfBounds; Lenient wraps the address around.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryAccess {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+// Byte order of the opcodes in ROM files. CHIP-8 is big-endian; Little is
+// for tools that emit byte-swapped images, which are swapped back on load.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Big,
+    Little,
+}
+
+// A labeled span of memory; `end` is inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub name: &'static str,
+    pub start: u16,
+    pub end: u16,
+}
+
+// Executed instruction counts. `families` is indexed by the high nibble of the
+// opcode; the 8xyN and FxKK families are further split by N and KK. An
+// instruction that waits, like Fx0A, counts once when it completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeStats {
+    pub families: [u64; 0x10],
+    pub alu: [u64; 0x10],
+    pub misc: [u64; 0x100],
+}
+
+impl OpcodeStats {
+    pub fn total(&self) -> u64 {
+        self.families.iter().sum()
+    }
+
+    fn record(&mut self, o: u16, kk: u8, n: u16) {
+        self.families[o as usize] += 1;
+        match o {
+            0x8 => self.alu[n as usize] += 1,
+            0xF => self.misc[kk as usize] += 1,
+            _ => {}
+        }
+    }
+}
+
+impl Default for OpcodeStats {
+    fn default() -> Self {
+        OpcodeStats {
+            families: [0; 0x10],
+            alu: [0; 0x10],
+            misc: [0; 0x100],
+        }
+    }
+}
+
+// Registers in full; memory and the display as short hashes, enough to tell
+// two states apart in a failing assertion.
+impl fmt::Debug for Chip8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Chip8")
+            .field("pc", &format_args!("{:#05X}", self.pc))
+            .field("I", &format_args!("{:#05X}", self.I))
+            .field("sp", &self.sp)
+            .field("V", &format_args!("{:02X?}", self.V))
+            .field("DT", &self.DT)
+            .field("ST", &self.ST)
+            .field("memory", &format_args!("{:08x}", short_hash(&self.memory)))
+            .field(
+                "display",
+                &format_args!("{:08x}", short_hash(&self.display)),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+fn short_hash<T: Hash + ?Sized>(value: &T) -> u32 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish() as u32
+}
+
+// Machines compare equal when their architectural state and the settings the
+// next cycle depends on match; the RNG and host-side bookkeeping such as
+// statistics, logs and callbacks are not part of the comparison.
+impl PartialEq for Chip8 {
+    fn eq(&self, other: &Self) -> bool {
+        self.memory == other.memory
+            && self.V == other.V
+            && self.stack == other.stack
+            && self.display == other.display
+            && self.keys == other.keys
+            && self.I == other.I
+            && self.pc == other.pc
+            && self.sp == other.sp
+            && self.DT == other.DT
+            && self.ST == other.ST
+            && self.halted == other.halted
+            && self.exited == other.exited
+            && self.waiting_for_key == other.waiting_for_key
+            && self.vblank == other.vblank
+            && self.quirks == other.quirks
+            && self.memory_access == other.memory_access
+            && self.draw_mode == other.draw_mode
+    }
+}
+
+impl Default for Chip8 {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -305,6 +1297,57 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
-    #[error("ROM file is too big: {0} bytes expected < 3583 bytes.")]
-    ROMIsTooBig(u64),
+    #[error("ROM file is too big: {0} bytes, at most {1} bytes fit.")]
+    ROMIsTooBig(u64, u64),
+    #[error("ROM of {0} bytes does not fit in memory at {1:#05X}.")]
+    ROMDoesNotFit(u64, u16),
+    #[error("Memory access out of bounds at {0:#05X}.")]
+    AddressOutOfBounds(u16),
+    #[error("Stack overflow.")]
+    StackOverflow,
+    #[error("Stack underflow.")]
+    StackUnderflow,
+    #[error("Malformed symbol on line {0}, expected NAME 0xADDR.")]
+    InvalidSymbol(usize),
+    // Only produced by the asm feature, but always present so that enabling
+    // the feature does not break exhaustive matches downstream.
+    #[error("Assembly error on line {0}: {1}.")]
+    Assemble(usize, String),
+    #[error("Unrecognized opcode: {0:#06X}.")]
+    UnknownOpcode(u16),
+    #[error("Pixel ({0}, {1}) is outside the display.")]
+    PixelOutOfBounds(usize, usize),
+    #[error("Frame {0} is outside the recorded range.")]
+    FrameNotRecorded(u64),
+}
+
+// No instruction can break the stack invariants, so these corrupt the fields
+// directly.
+#[cfg(all(test, debug_assertions))]
+mod tests {
+    use super::*;
+
+    fn chip8_at_ld() -> Chip8 {
+        let mut chip8 = Chip8::with_seed(0);
+        // LD V0, 1
+        chip8.load_from_bytes(&[0x60, 0x01]).unwrap();
+        chip8
+    }
+
+    #[test]
+    #[should_panic(expected = "past the end of the stack")]
+    fn stack_pointer_past_the_stack_trips_the_check() {
+        let mut chip8 = chip8_at_ld();
+        chip8.sp = STACK_SIZE as u8;
+        let _ = chip8.cycle();
+    }
+
+    #[test]
+    #[should_panic(expected = "return address past the end of memory")]
+    fn return_address_past_memory_trips_the_check() {
+        let mut chip8 = chip8_at_ld();
+        chip8.sp = 1;
+        chip8.stack[1] = MEMORY_SIZE as u16 + 2;
+        let _ = chip8.cycle();
+    }
 }
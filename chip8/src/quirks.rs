@@ -0,0 +1,76 @@
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    // 8xy1, 8xy2 and 8xy3 reset VF to 0.
+    pub vf_reset: bool,
+    // Fx55 and Fx65 leave I pointing past the last register transferred.
+    pub memory: bool,
+    // 8xy6 and 8xyE shift Vx in place instead of shifting Vy into Vx.
+    pub shifting: bool,
+    // Bnnn jumps to xnn + Vx instead of nnn + V0.
+    pub jumping: bool,
+    // Sprites are clipped at the screen edges instead of wrapping around.
+    pub clipping: bool,
+    // Dxyn waits for the next timer tick before drawing.
+    pub display_wait: bool,
+}
+
+impl Quirks {
+    // The original COSMAC VIP interpreter.
+    pub const fn chip8() -> Self {
+        Quirks {
+            vf_reset: true,
+            memory: true,
+            shifting: false,
+            jumping: false,
+            clipping: true,
+            display_wait: true,
+        }
+    }
+
+    // SUPER-CHIP 1.1 on the HP48.
+    pub const fn schip() -> Self {
+        Quirks {
+            vf_reset: false,
+            memory: false,
+            shifting: true,
+            jumping: true,
+            clipping: true,
+            display_wait: false,
+        }
+    }
+
+    // Octo's defaults, which most modern CHIP-8 programs are written against.
+    pub const fn octo() -> Self {
+        Quirks {
+            vf_reset: false,
+            memory: false,
+            shifting: true,
+            jumping: false,
+            clipping: true,
+            display_wait: false,
+        }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Quirks {
+            vf_reset: false,
+            memory: false,
+            shifting: true,
+            jumping: false,
+            clipping: false,
+            display_wait: false,
+        }
+    }
+}
+
+// Lowercase hex SHA-256 of a ROM image, the key for per-ROM data.
+pub fn rom_hash(rom: &[u8]) -> String {
+    Sha256::digest(rom)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
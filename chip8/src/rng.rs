@@ -0,0 +1,23 @@
+use rand::{Rng, RngCore};
+
+// Source of the random bytes used by Cxkk.
+pub trait Chip8Rng: Send {
+    fn next_byte(&mut self) -> u8;
+    fn clone_box(&self) -> Box<dyn Chip8Rng>;
+}
+
+impl<R: RngCore + Clone + Send + 'static> Chip8Rng for R {
+    fn next_byte(&mut self) -> u8 {
+        self.gen()
+    }
+
+    fn clone_box(&self) -> Box<dyn Chip8Rng> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Chip8Rng> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
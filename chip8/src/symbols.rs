@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::{Error, Result};
+
+// Address labels from an assembler's symbol file, one `NAME 0xADDR` pair per
+// line. Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SymbolMap {
+    symbols: BTreeMap<u16, String>,
+}
+
+impl SymbolMap {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SymbolMap> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> Result<SymbolMap> {
+        let mut symbols = BTreeMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let (Some(name), Some(addr), None) = (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(Error::InvalidSymbol(i + 1));
+            };
+            let addr = addr
+                .strip_prefix("0x")
+                .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+                .ok_or(Error::InvalidSymbol(i + 1))?;
+            symbols.insert(addr, name.to_string());
+        }
+        Ok(SymbolMap { symbols })
+    }
+
+    pub fn get(&self, addr: u16) -> Option<&str> {
+        self.symbols.get(&addr).map(String::as_str)
+    }
+}
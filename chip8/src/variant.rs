@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use crate::{Quirks, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Chip8,
+    SuperChip,
+    XoChip,
+}
+
+impl Variant {
+    // Guesses the variant from the opcodes present in the image. Only aligned
+    // words are inspected, so this is a heuristic rather than a disassembly.
+    pub fn from_rom(rom: &[u8]) -> Variant {
+        let mut variant = Variant::Chip8;
+        for word in rom.chunks_exact(2) {
+            let opcode = u16::from_be_bytes([word[0], word[1]]);
+            match (opcode >> 12, opcode & 0xFF, opcode & 0xF) {
+                // 00Dn, 5xy2, 5xy3, F000 NNNN, Fn01, F002, Fx3A
+                (0x0, 0xD0..=0xDF, _) if opcode & 0x0F00 == 0 => return Variant::XoChip,
+                (0x5, _, 0x2 | 0x3) => return Variant::XoChip,
+                (0xF, 0x00 | 0x01 | 0x02 | 0x3A, _) => return Variant::XoChip,
+                // 00Cn, 00FB-00FF, Fx30, Fx75, Fx85
+                (0x0, 0xC0..=0xCF | 0xFB..=0xFF, _) if opcode & 0x0F00 == 0 => {
+                    variant = Variant::SuperChip
+                }
+                (0xF, 0x30 | 0x75 | 0x85, _) => variant = Variant::SuperChip,
+                _ => {}
+            }
+        }
+        variant
+    }
+
+    pub fn quirks(self) -> Quirks {
+        match self {
+            Variant::Chip8 => Quirks::chip8(),
+            Variant::SuperChip => Quirks::schip(),
+            Variant::XoChip => Quirks {
+                vf_reset: false,
+                memory: true,
+                shifting: false,
+                jumping: false,
+                clipping: false,
+                display_wait: false,
+            },
+        }
+    }
+}
+
+pub fn detect_variant<P: AsRef<Path>>(path: P) -> Result<Variant> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("xo8" | "c8x") => return Ok(Variant::XoChip),
+        Some("sc8" | "schip") => return Ok(Variant::SuperChip),
+        _ => {}
+    }
+    let rom = std::fs::read(path)?;
+    Ok(Variant::from_rom(&rom))
+}
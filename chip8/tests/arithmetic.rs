@@ -0,0 +1,68 @@
+mod common;
+
+use common::{assert_registers, run_program};
+
+#[test]
+fn add_reg_without_carry_clears_vf() {
+    // V0 = 0x10, V1 = 0x20, VF = 1, V0 += V1
+    let chip8 = run_program(&[0x6010, 0x6120, 0x6F01, 0x8014]);
+    assert_registers(&chip8, &[(0x0, 0x30), (0xF, 0)]);
+}
+
+#[test]
+fn add_reg_with_carry_sets_vf() {
+    // V0 = 0xFF, V1 = 0x02, V0 += V1
+    let chip8 = run_program(&[0x60FF, 0x6102, 0x8014]);
+    assert_registers(&chip8, &[(0x0, 0x01), (0xF, 1)]);
+}
+
+#[test]
+fn add_reg_into_vf_keeps_the_carry() {
+    // VF = 0xFF, V1 = 0x02, VF += V1
+    let chip8 = run_program(&[0x6FFF, 0x6102, 0x8F14]);
+    assert_registers(&chip8, &[(0xF, 1)]);
+}
+
+#[test]
+fn sub_without_borrow_sets_vf() {
+    // V0 = 0x30, V1 = 0x10, V0 -= V1
+    let chip8 = run_program(&[0x6030, 0x6110, 0x8015]);
+    assert_registers(&chip8, &[(0x0, 0x20), (0xF, 1)]);
+}
+
+#[test]
+fn sub_of_equal_values_is_not_a_borrow() {
+    // V0 = 0x42, V1 = 0x42, V0 -= V1
+    let chip8 = run_program(&[0x6042, 0x6142, 0x8015]);
+    assert_registers(&chip8, &[(0x0, 0x00), (0xF, 1)]);
+}
+
+#[test]
+fn sub_with_borrow_clears_vf() {
+    // V0 = 0x10, V1 = 0x30, VF = 1, V0 -= V1
+    let chip8 = run_program(&[0x6010, 0x6130, 0x6F01, 0x8015]);
+    assert_registers(&chip8, &[(0x0, 0xE0), (0xF, 0)]);
+}
+
+#[test]
+fn add_byte_wraps_and_leaves_vf_alone() {
+    // V0 = 0xFF, VF = 0x07, V0 += 0x02
+    let chip8 = run_program(&[0x60FF, 0x6F07, 0x7002]);
+    assert_registers(&chip8, &[(0x0, 0x01), (0xF, 0x07)]);
+}
+
+#[test]
+fn flag_wins_over_the_result_in_vf() {
+    // VF = 0xFF, V0 = 0x03, VF += V0: the sum would be 0x02
+    assert_registers(&run_program(&[0x6FFF, 0x6003, 0x8F04]), &[(0xF, 1)]);
+    // VF = 0x10, V0 = 0x20, VF += V0: the sum would be 0x30
+    assert_registers(&run_program(&[0x6F10, 0x6020, 0x8F04]), &[(0xF, 0)]);
+    // VF = 0x30, V0 = 0x10, VF -= V0: the difference would be 0x20
+    assert_registers(&run_program(&[0x6F30, 0x6010, 0x8F05]), &[(0xF, 1)]);
+    // VF = 0x10, V0 = 0x30, VF = V0 - VF: the difference would be 0x20
+    assert_registers(&run_program(&[0x6F10, 0x6030, 0x8F07]), &[(0xF, 1)]);
+    // VF = 0x02, VF >>= 1: the result would be 0x01
+    assert_registers(&run_program(&[0x6F02, 0x8F06]), &[(0xF, 0)]);
+    // VF = 0x41, VF <<= 1: the result would be 0x82
+    assert_registers(&run_program(&[0x6F41, 0x8F0E]), &[(0xF, 0)]);
+}
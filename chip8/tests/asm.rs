@@ -0,0 +1,18 @@
+#![cfg(feature = "asm")]
+
+mod common;
+
+use chip8::asm::{add_vx_vy, ld_vx_byte, program};
+use chip8::Chip8;
+use common::{assert_registers, run};
+
+#[test]
+fn encoded_program_runs() {
+    let rom = program(&[ld_vx_byte(0, 0x21), ld_vx_byte(1, 0x21), add_vx_vy(0, 1)]);
+    assert_eq!(rom, [0x60, 0x21, 0x61, 0x21, 0x80, 0x14]);
+
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_from_bytes(&rom).unwrap();
+    run(&mut chip8, 3);
+    assert_registers(&chip8, &[(0, 0x42), (1, 0x21), (0xF, 0)]);
+}
@@ -0,0 +1,76 @@
+// Fixtures shared by the integration tests. Each test binary uses only some
+// of them.
+#![allow(dead_code)]
+
+use chip8::{Chip8, Chip8Rng, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+// A machine with a fixed RNG seed and `opcodes` loaded at 0x200.
+pub fn chip8_with(opcodes: &[u16]) -> Chip8 {
+    let rom: Vec<u8> = opcodes.iter().flat_map(|op| op.to_be_bytes()).collect();
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_from_bytes(&rom).unwrap();
+    chip8
+}
+
+// Yields the given bytes in order, starting over after the last one, so Cxkk
+// produces known values.
+#[derive(Debug, Clone)]
+pub struct SequenceRng {
+    values: Vec<u8>,
+    next: usize,
+}
+
+impl SequenceRng {
+    pub fn new(values: &[u8]) -> Self {
+        assert!(!values.is_empty(), "SequenceRng needs at least one value.");
+        SequenceRng {
+            values: values.to_vec(),
+            next: 0,
+        }
+    }
+}
+
+impl Chip8Rng for SequenceRng {
+    fn next_byte(&mut self) -> u8 {
+        let value = self.values[self.next];
+        self.next = (self.next + 1) % self.values.len();
+        value
+    }
+
+    fn clone_box(&self) -> Box<dyn Chip8Rng> {
+        Box::new(self.clone())
+    }
+}
+
+pub fn run(chip8: &mut Chip8, cycles: usize) {
+    for _ in 0..cycles {
+        chip8.cycle().unwrap();
+    }
+}
+
+// Runs every opcode of a `chip8_with` program once.
+pub fn run_program(opcodes: &[u16]) -> Chip8 {
+    let mut chip8 = chip8_with(opcodes);
+    run(&mut chip8, opcodes.len());
+    chip8
+}
+
+#[track_caller]
+pub fn assert_registers(chip8: &Chip8, expected: &[(usize, u8)]) {
+    for &(x, value) in expected {
+        assert_eq!(chip8.registers()[x], value, "V{x:X}");
+    }
+}
+
+// `rows` draws the display top to bottom, with '#' for lit pixels and '.'
+// for dark ones. Rows and columns past the picture must be dark.
+#[track_caller]
+pub fn assert_display(chip8: &Chip8, rows: &[&str]) {
+    for y in 0..DISPLAY_HEIGHT {
+        let row = rows.get(y).copied().unwrap_or("").as_bytes();
+        for x in 0..DISPLAY_WIDTH {
+            let expected = row.get(x) == Some(&b'#');
+            assert_eq!(chip8.get_pixel(x, y), Some(expected), "pixel ({x}, {y})");
+        }
+    }
+}
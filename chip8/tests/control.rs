@@ -0,0 +1,148 @@
+mod common;
+
+use chip8::{Chip8, Error, Quirks};
+use common::{chip8_with, run};
+
+#[test]
+fn jump_to_self_halts() {
+    // ADD V0, 1; JP 0x202
+    let mut chip8 = chip8_with(&[0x7001, 0x1202]);
+    run(&mut chip8, 1);
+    assert!(!chip8.is_halted());
+    run(&mut chip8, 1);
+    assert!(chip8.is_halted());
+    run(&mut chip8, 5);
+    assert_eq!(chip8.registers()[0], 1);
+}
+
+#[test]
+fn fast_forward_stops_at_a_halt() {
+    // JP 0x200: halts during the first frame
+    let mut chip8 = chip8_with(&[0x1200]);
+    chip8.fast_forward(100).unwrap();
+    assert!(chip8.is_halted());
+    assert_eq!(chip8.frame_count(), 1);
+
+    let mut chip8 = chip8_with(&[0x7001, 0x1200]);
+    chip8.fast_forward(3).unwrap();
+    assert!(!chip8.is_halted());
+    assert_eq!(chip8.frame_count(), 3);
+}
+
+#[test]
+fn set_pc_runs_from_the_new_address() {
+    // LD V0, 0x11 at 0x200 and LD V0, 0x42 at 0x300
+    let mut chip8 = chip8_with(&[0x6011]);
+    chip8.write_rom_region(&[0x60, 0x42], 0x300).unwrap();
+    chip8.set_pc(0x300).unwrap();
+    run(&mut chip8, 1);
+    assert_eq!(chip8.registers()[0], 0x42);
+    assert_eq!(chip8.pc(), 0x302);
+
+    assert!(matches!(
+        chip8.set_pc(0x1000),
+        Err(Error::AddressOutOfBounds(0x1000))
+    ));
+    assert_eq!(chip8.pc(), 0x302);
+}
+
+#[test]
+fn jump_with_offset_wraps_past_the_end_of_memory() {
+    for jumping in [false, true] {
+        // V0 = 0x10, VF = 0x10, JP V0, 0xFFE: 0xFFE + 0x10 wraps to 0x00E
+        let mut chip8 = chip8_with(&[0x6010, 0x6F10, 0xBFFE]);
+        chip8.set_quirks(Quirks {
+            jumping,
+            ..Quirks::default()
+        });
+        run(&mut chip8, 3);
+        assert_eq!(chip8.pc(), 0x00E, "jumping quirk {jumping}");
+    }
+}
+
+#[test]
+fn exit_stops_the_program() {
+    // ADD V0, 1; EXIT; ADD V0, 1
+    let mut chip8 = chip8_with(&[0x7001, 0x00FD, 0x7001]);
+    run(&mut chip8, 2);
+    assert!(chip8.has_exited());
+    assert_eq!(chip8.pc(), 0x204);
+    run(&mut chip8, 3);
+    assert_eq!(chip8.pc(), 0x204);
+    assert_eq!(chip8.registers()[0], 1);
+}
+
+#[test]
+fn halt_stops_cycles_until_resumed() {
+    // ADD V0, 1; JP 0x200
+    let mut chip8 = chip8_with(&[0x7001, 0x1200]);
+    chip8.halt();
+    assert!(chip8.is_halted());
+    run(&mut chip8, 4);
+    assert_eq!(chip8.pc(), 0x200);
+    assert_eq!(chip8.registers()[0], 0);
+
+    chip8.resume();
+    assert!(!chip8.is_halted());
+    run(&mut chip8, 4);
+    assert_eq!(chip8.registers()[0], 2);
+}
+
+#[test]
+fn stack_errors_instead_of_overrunning() {
+    // CALL 0x200, forever
+    let mut chip8 = chip8_with(&[0x2200]);
+    run(&mut chip8, 15);
+    assert!(matches!(chip8.cycle(), Err(Error::StackOverflow)));
+
+    // RET with nothing to return to
+    let mut chip8 = chip8_with(&[0x00EE]);
+    assert!(matches!(chip8.cycle(), Err(Error::StackUnderflow)));
+}
+
+#[test]
+fn short_roms_halt_on_the_zero_word() {
+    for rom in [&[][..], &[0x00][..]] {
+        let mut chip8 = Chip8::with_seed(0);
+        chip8.load_from_bytes(rom).unwrap();
+        let result = chip8.cycle().unwrap();
+        assert_eq!(result.opcode, 0x0000, "{rom:?}");
+        assert!(chip8.is_halted(), "{rom:?}");
+        assert_eq!(chip8.pc(), 0x200, "{rom:?}");
+        // Further cycles stay put.
+        chip8.cycle().unwrap();
+        assert_eq!(chip8.pc(), 0x200, "{rom:?}");
+    }
+}
+
+#[test]
+fn sys_is_ignored() {
+    // SYS 0x123; LD V0, 1
+    let mut chip8 = chip8_with(&[0x0123, 0x6001]);
+    run(&mut chip8, 2);
+    assert!(!chip8.is_halted());
+    assert_eq!(chip8.registers()[0], 1);
+    assert_eq!(chip8.pc(), 0x204);
+}
+
+#[test]
+fn conditional_skips_compare_registers_and_bytes() {
+    // Each skip is followed by ADD V2, 1, which only runs when not skipped.
+    let cases = [
+        (0x3005, false), // SE V0, 5
+        (0x3006, true),  // SE V0, 6
+        (0x4005, true),  // SNE V0, 5
+        (0x4006, false), // SNE V0, 6
+        (0x5010, false), // SE V0, V1
+        (0x5030, true),  // SE V0, V3
+        (0x9010, true),  // SNE V0, V1
+        (0x9030, false), // SNE V0, V3
+    ];
+    for (skip, runs_add) in cases {
+        // LD V0, 5; LD V1, 5; LD V3, 6
+        let mut chip8 = chip8_with(&[0x6005, 0x6105, 0x6306, skip, 0x7201]);
+        run(&mut chip8, 5);
+        let expected = if runs_add { 1 } else { 0 };
+        assert_eq!(chip8.registers()[2], expected, "{skip:04X}");
+    }
+}
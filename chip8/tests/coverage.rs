@@ -0,0 +1,105 @@
+#![cfg(feature = "test-coverage")]
+// Every included file declares its own `mod common`.
+#![allow(clippy::duplicate_mod)]
+
+// Each file under tests/ builds to its own binary with its own coverage
+// record, so this one pulls the whole suite into a single harness. The check
+// below runs that harness again in a child process and reads back what the
+// suite covered.
+
+#[path = "arithmetic.rs"]
+mod arithmetic;
+#[path = "asm.rs"]
+mod asm;
+#[path = "control.rs"]
+mod control;
+#[path = "decode.rs"]
+mod decode;
+#[path = "display.rs"]
+mod display;
+#[path = "ffi.rs"]
+mod ffi;
+#[path = "keys.rs"]
+mod keys;
+#[path = "load.rs"]
+mod load;
+#[path = "memory.rs"]
+mod memory;
+#[path = "quirks.rs"]
+mod quirks;
+#[path = "random.rs"]
+mod random;
+#[path = "replay.rs"]
+mod replay;
+#[path = "snapshot.rs"]
+mod snapshot;
+#[path = "stats.rs"]
+mod stats;
+#[path = "timing.rs"]
+mod timing;
+#[path = "variant.rs"]
+mod variant;
+
+use std::path::Path;
+use std::process::Command;
+
+use chip8::coverage::{uncovered_in_report, REPORT_VAR};
+
+const SUITE: &[&str] = &[
+    "arithmetic",
+    "asm",
+    "control",
+    "decode",
+    "display",
+    "ffi",
+    "keys",
+    "load",
+    "memory",
+    "quirks",
+    "random",
+    "replay",
+    "snapshot",
+    "stats",
+    "timing",
+    "variant",
+];
+
+const CHECK: &str = "every_opcode_family_is_exercised";
+
+#[test]
+fn every_opcode_family_is_exercised() {
+    let report = std::env::temp_dir().join(format!("chip8-coverage-{}", std::process::id()));
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--skip", CHECK])
+        .env(REPORT_VAR, &report)
+        .output()
+        .unwrap();
+    let text = std::fs::read_to_string(&report).unwrap_or_default();
+    let _ = std::fs::remove_file(&report);
+    assert!(
+        output.status.success(),
+        "the suite failed:\n{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    let uncovered = uncovered_in_report(&text);
+    assert!(
+        uncovered.is_empty(),
+        "no test ran an instruction from families {uncovered:X?}"
+    );
+}
+
+// A test file left out of the modules above would go uncounted.
+#[test]
+fn suite_includes_every_test_file() {
+    let tests = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut files: Vec<String> = std::fs::read_dir(tests)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .map(|path| path.file_stem().unwrap().to_string_lossy().into_owned())
+        .filter(|name| name != "coverage")
+        .collect();
+    files.sort();
+    assert_eq!(files, SUITE);
+}
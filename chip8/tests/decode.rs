@@ -0,0 +1,193 @@
+use chip8::{
+    analyze, decode, disassemble, disassemble_with_symbols, trace_line, Chip8, Error, Instruction,
+    SymbolMap,
+};
+
+#[test]
+fn decode_maps_representative_opcodes() {
+    assert_eq!(decode(0x00E0), Instruction::Cls);
+    assert_eq!(decode(0x00EE), Instruction::Ret);
+    assert_eq!(decode(0x0123), Instruction::Sys(0x123));
+    assert_eq!(decode(0x1ABC), Instruction::Jp(0xABC));
+    assert_eq!(decode(0x3A42), Instruction::SeByte { x: 0xA, kk: 0x42 });
+    assert_eq!(decode(0x8124), Instruction::AddReg { x: 0x1, y: 0x2 });
+    assert_eq!(decode(0x812E), Instruction::Shl { x: 0x1, y: 0x2 });
+    assert_eq!(
+        decode(0xD125),
+        Instruction::Drw {
+            x: 0x1,
+            y: 0x2,
+            n: 0x5
+        }
+    );
+    assert_eq!(decode(0xE3A1), Instruction::Sknp { x: 0x3 });
+    assert_eq!(decode(0xF565), Instruction::LdVxI { x: 0x5 });
+}
+
+#[test]
+fn decode_reports_unassigned_opcodes() {
+    for opcode in [0x5121, 0x8128, 0x9121, 0xE100, 0xF1FF] {
+        assert_eq!(decode(opcode), Instruction::Unknown(opcode));
+    }
+}
+
+// The decoder as it was before dispatching on the high nibble, kept as the
+// reference for the sweep below.
+fn tuple_decode(opcode: u16) -> Instruction {
+    let o = (opcode & 0xF000) >> 12;
+    let nnn = opcode & 0x0FFF;
+    let n = (opcode & 0x000F) as u8;
+    let x = ((opcode & 0x0F00) >> 8) as u8;
+    let y = ((opcode & 0x00F0) >> 4) as u8;
+    let kk = (opcode & 0x00FF) as u8;
+
+    match (o, kk, n) {
+        (0, 0xE0, _) => Instruction::Cls,
+        (0, 0xEE, _) => Instruction::Ret,
+        (0, 0xFD, _) => Instruction::Exit,
+        (0, _, _) => Instruction::Sys(nnn),
+        (1, _, _) => Instruction::Jp(nnn),
+        (2, _, _) => Instruction::Call(nnn),
+        (3, _, _) => Instruction::SeByte { x, kk },
+        (4, _, _) => Instruction::SneByte { x, kk },
+        (5, _, 0) => Instruction::SeReg { x, y },
+        (6, _, _) => Instruction::LdByte { x, kk },
+        (7, _, _) => Instruction::AddByte { x, kk },
+        (8, _, 0) => Instruction::LdReg { x, y },
+        (8, _, 1) => Instruction::Or { x, y },
+        (8, _, 2) => Instruction::And { x, y },
+        (8, _, 3) => Instruction::Xor { x, y },
+        (8, _, 4) => Instruction::AddReg { x, y },
+        (8, _, 5) => Instruction::Sub { x, y },
+        (8, _, 6) => Instruction::Shr { x, y },
+        (8, _, 7) => Instruction::Subn { x, y },
+        (8, _, 0xE) => Instruction::Shl { x, y },
+        (9, _, 0) => Instruction::SneReg { x, y },
+        (0xA, _, _) => Instruction::LdI(nnn),
+        (0xB, _, _) => Instruction::JpV0(nnn),
+        (0xC, _, _) => Instruction::Rnd { x, kk },
+        (0xD, _, _) => Instruction::Drw { x, y, n },
+        (0xE, 0x9E, _) => Instruction::Skp { x },
+        (0xE, 0xA1, _) => Instruction::Sknp { x },
+        (0xF, 0x07, _) => Instruction::LdVxDt { x },
+        (0xF, 0x0A, _) => Instruction::LdVxK { x },
+        (0xF, 0x15, _) => Instruction::LdDtVx { x },
+        (0xF, 0x18, _) => Instruction::LdStVx { x },
+        (0xF, 0x1E, _) => Instruction::AddIVx { x },
+        (0xF, 0x29, _) => Instruction::LdFVx { x },
+        (0xF, 0x33, _) => Instruction::LdBVx { x },
+        (0xF, 0x55, _) => Instruction::LdIVx { x },
+        (0xF, 0x65, _) => Instruction::LdVxI { x },
+        _ => Instruction::Unknown(opcode),
+    }
+}
+
+#[test]
+fn decode_matches_the_tuple_dispatch_for_every_opcode() {
+    for opcode in 0..=u16::MAX {
+        assert_eq!(decode(opcode), tuple_decode(opcode), "{opcode:04X}");
+    }
+}
+
+#[test]
+fn disassemble_lists_each_word() {
+    // CLS; LD I, 0x22A; JP 0x204; a trailing odd byte
+    let rom = [0x00, 0xE0, 0xA2, 0x2A, 0x12, 0x04, 0xFF];
+    assert_eq!(
+        disassemble(&rom, 0x200),
+        "\
+200  00E0  CLS
+202  A22A  LD I, 0x22A
+204  1204  JP 0x204
+206  FF    DB 0xFF
+"
+    );
+}
+
+#[test]
+fn analyze_flags_undecodable_words() {
+    // CLS; LD V0, 0x01; 0x5121 (unassigned); JP 0x200; 0xFFFF (unassigned)
+    let rom = [0x00, 0xE0, 0x60, 0x01, 0x51, 0x21, 0x12, 0x00, 0xFF, 0xFF];
+    let analysis = analyze(&rom);
+    assert_eq!(analysis.unknown, vec![0x204, 0x208]);
+    assert_eq!(analysis.total(), 5);
+    let mut families = [0; 0x10];
+    families[0x0] = 1;
+    families[0x1] = 1;
+    families[0x5] = 1;
+    families[0x6] = 1;
+    families[0xF] = 1;
+    assert_eq!(analysis.families, families);
+}
+
+#[test]
+fn analyze_ignores_a_trailing_odd_byte() {
+    let analysis = analyze(&[0x00, 0xE0, 0xFF]);
+    assert_eq!(analysis.total(), 1);
+    assert!(analysis.unknown.is_empty());
+}
+
+const SYMBOLS: &str = "\
+# main loop
+start 0x200
+
+sprite 0x20A
+";
+
+#[test]
+fn symbol_map_resolves_labels() {
+    let symbols = SymbolMap::parse(SYMBOLS).unwrap();
+    assert_eq!(symbols.get(0x200), Some("start"));
+    assert_eq!(symbols.get(0x20A), Some("sprite"));
+    assert_eq!(symbols.get(0x202), None);
+
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_symbols(symbols);
+    assert_eq!(chip8.symbol_for(0x20A), Some("sprite"));
+    assert_eq!(chip8.symbol_for(0x300), None);
+}
+
+#[test]
+fn symbol_map_reports_the_bad_line() {
+    let err = SymbolMap::parse("start 0x200\nsprite 20A\n").unwrap_err();
+    assert!(matches!(err, Error::InvalidSymbol(2)), "{err:?}");
+    let err = SymbolMap::parse("start 0x200 extra\n").unwrap_err();
+    assert!(matches!(err, Error::InvalidSymbol(1)), "{err:?}");
+}
+
+#[test]
+fn disassemble_with_symbols_annotates_targets() {
+    // LD I, sprite; JP start
+    let rom = [0xA2, 0x0A, 0x12, 0x00];
+    let symbols = SymbolMap::parse(SYMBOLS).unwrap();
+    assert_eq!(
+        disassemble_with_symbols(&rom, 0x200, &symbols),
+        "\
+start:
+200  A20A  LD I, 0x20A  ; sprite
+202  1200  JP 0x200  ; start
+"
+    );
+}
+
+#[test]
+fn trace_line_formats_pc_opcode_and_mnemonic() {
+    assert_eq!(trace_line(0x200, 0x00E0), "200  00E0  CLS");
+    assert_eq!(trace_line(0x2A4, 0xD125), "2A4  D125  DRW V1, V2, 5");
+    assert_eq!(trace_line(0x00E, 0xA22A), "00E  A22A  LD I, 0x22A");
+}
+
+#[test]
+fn step_returns_the_executed_instruction() {
+    // LD VA, 0x3C; JP 0x202
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_from_bytes(&[0x6A, 0x3C, 0x12, 0x02]).unwrap();
+    assert_eq!(
+        chip8.step().unwrap(),
+        Instruction::LdByte { x: 0xA, kk: 0x3C }
+    );
+    assert_eq!(chip8.registers()[0xA], 0x3C);
+    assert_eq!(chip8.step().unwrap(), Instruction::Jp(0x202));
+    // Halted on the self-jump, nothing more is fetched.
+    assert_eq!(chip8.step().unwrap(), Instruction::Sys(0));
+}
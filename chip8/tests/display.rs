@@ -0,0 +1,245 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use chip8::{
+    Chip8, DrawMode, Error, MemoryAccess, Quirks, DISPLAY_HEIGHT, DISPLAY_SIZE, DISPLAY_WIDTH,
+};
+use common::{assert_display, chip8_with, run, run_program};
+
+#[test]
+fn font_sprite_draws_at_the_origin() {
+    // V0 = 0, I = sprite 0, draw 5 rows at (V0, V0)
+    let chip8 = run_program(&[0x6000, 0xF029, 0xD005]);
+    assert_display(&chip8, &["####", "#..#", "#..#", "#..#", "####"]);
+}
+
+#[test]
+fn step_result_reports_display_changes() {
+    // V0 = 0, I = sprite 0, draw
+    let mut chip8 = chip8_with(&[0x6000, 0xF029, 0xD005]);
+    assert!(!chip8.cycle().unwrap().display_changed);
+    chip8.cycle().unwrap();
+    let result = chip8.cycle().unwrap();
+    assert_eq!(result.opcode, 0xD005);
+    assert!(result.display_changed);
+}
+
+#[test]
+fn or_mode_keeps_pixels_and_leaves_vf_alone() {
+    // V0 = 0, VF = 5, I = sprite 0, draw twice
+    let mut chip8 = chip8_with(&[0x6000, 0x6F05, 0xF029, 0xD005, 0xD005]);
+    chip8.set_draw_mode(DrawMode::Or);
+    run(&mut chip8, 5);
+    assert_display(&chip8, &["####", "#..#", "#..#", "#..#", "####"]);
+    assert_eq!(chip8.vf(), 5);
+}
+
+#[test]
+fn collision_log_records_pixels_turned_off() {
+    // V0 = 0, I = sprite 0, draw, V1 = 1, I = sprite 1, draw
+    let mut chip8 = chip8_with(&[0x6000, 0xF029, 0xD005, 0x6101, 0xF129, 0xD005]);
+    chip8.set_collision_log(true);
+    run(&mut chip8, 3);
+    assert!(chip8.last_collisions().is_empty());
+    run(&mut chip8, 3);
+    let mut collisions = chip8.last_collisions().to_vec();
+    collisions.sort();
+    assert_eq!(collisions, [(1, 4), (2, 0), (2, 4), (3, 4)]);
+    assert_eq!(chip8.vf(), 1);
+}
+
+#[test]
+fn draw_sprite_draws_and_reports_collisions() {
+    let mut chip8 = Chip8::with_seed(0);
+    assert!(!chip8.draw_sprite(2, 1, &[0xC0, 0x40]));
+    assert_display(&chip8, &["", "..##", "...#"]);
+    assert!(chip8.draw_sprite(2, 1, &[0x80]));
+    assert_display(&chip8, &["", "...#", "...#"]);
+    assert_eq!(chip8.vf(), 0);
+}
+
+#[test]
+fn sprite_with_i_past_the_end_of_memory_draws_nothing() {
+    // I = 0xFFF, V0 = 0xFF, I += V0, draw 1 row
+    let mut chip8 = chip8_with(&[0xAFFF, 0x60FF, 0xF01E, 0xD011]);
+    run(&mut chip8, 4);
+    assert_display(&chip8, &[]);
+    assert_eq!(chip8.vf(), 0);
+}
+
+#[test]
+fn sprite_reading_past_the_end_of_memory_is_not_an_error() {
+    for memory_access in [MemoryAccess::Strict, MemoryAccess::Lenient] {
+        // I = 0xFFE, draw 5 rows: only 0xFFE and 0xFFF exist
+        let mut chip8 = chip8_with(&[0xAFFE, 0xD005]);
+        chip8.set_memory_access(memory_access);
+        run(&mut chip8, 2);
+        assert_eq!(chip8.pc(), 0x204, "{memory_access:?}");
+    }
+}
+
+#[test]
+fn display_wait_holds_dxyn_until_the_tick() {
+    // V0 = 0, I = sprite 0, draw
+    let mut chip8 = chip8_with(&[0x6000, 0xF029, 0xD005]);
+    chip8.set_quirks(Quirks {
+        display_wait: true,
+        ..Quirks::default()
+    });
+    run(&mut chip8, 2);
+    let held = chip8.cycle().unwrap();
+    assert_eq!(held.opcode, 0xD005);
+    assert!(!held.display_changed);
+    assert!(held.pc_changed_nonlinearly);
+    assert_eq!(chip8.pc(), 0x204);
+
+    chip8.timer();
+    let drawn = chip8.cycle().unwrap();
+    assert!(drawn.display_changed);
+    assert!(!drawn.pc_changed_nonlinearly);
+    assert_eq!(chip8.pc(), 0x206);
+    assert_eq!(chip8.opcode_stats().families[0xD], 1);
+}
+
+#[test]
+fn set_pixel_reads_back_and_rejects_out_of_bounds() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_pixel(3, 1, true).unwrap();
+    chip8
+        .set_pixel(DISPLAY_WIDTH - 1, DISPLAY_HEIGHT - 1, true)
+        .unwrap();
+    assert_eq!(chip8.get_pixel(3, 1), Some(true));
+    assert_eq!(
+        chip8.get_pixel(DISPLAY_WIDTH - 1, DISPLAY_HEIGHT - 1),
+        Some(true)
+    );
+    assert_eq!(chip8.get_pixel(4, 1), Some(false));
+    chip8.set_pixel(3, 1, false).unwrap();
+    assert_eq!(chip8.get_pixel(3, 1), Some(false));
+
+    assert!(matches!(
+        chip8.set_pixel(DISPLAY_WIDTH, 0, true),
+        Err(Error::PixelOutOfBounds(64, 0))
+    ));
+    assert!(matches!(
+        chip8.set_pixel(0, DISPLAY_HEIGHT, true),
+        Err(Error::PixelOutOfBounds(0, 32))
+    ));
+    assert_eq!(chip8.get_pixel(DISPLAY_WIDTH, 0), None);
+    assert_eq!(chip8.get_pixel(0, DISPLAY_HEIGHT), None);
+}
+
+// `opcodes` at 0x200 and `tail` ending at 0xFFF.
+fn chip8_with_tail(opcodes: &[u16], tail: &[u8]) -> Chip8 {
+    let mut rom = vec![0; Chip8::max_rom_size() as usize];
+    for (i, op) in opcodes.iter().enumerate() {
+        rom[i * 2..i * 2 + 2].copy_from_slice(&op.to_be_bytes());
+    }
+    let start = rom.len() - tail.len();
+    rom[start..].copy_from_slice(tail);
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_from_bytes(&rom).unwrap();
+    chip8
+}
+
+#[test]
+fn sprite_at_the_top_of_memory_draws_the_rows_that_exist() {
+    // I = 0xFFE, draw 5 rows twice: only 0xFFE and 0xFFF are drawn
+    let mut chip8 = chip8_with_tail(&[0xAFFE, 0xD005, 0xD005], &[0xF0, 0x90]);
+    run(&mut chip8, 2);
+    assert_display(&chip8, &["####", "#..#"]);
+    assert_eq!(chip8.vf(), 0);
+    run(&mut chip8, 1);
+    assert_display(&chip8, &[]);
+    assert_eq!(chip8.vf(), 1);
+}
+
+#[test]
+fn fifteen_row_sprite_near_the_top_of_memory_is_cut_short() {
+    // I = 0xFF8, draw 15 rows: only the 8 bytes up to 0xFFF are drawn
+    let tail = [0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x01];
+    let mut chip8 = chip8_with_tail(&[0xAFF8, 0xD00F], &tail);
+    run(&mut chip8, 2);
+    assert_display(
+        &chip8,
+        &[
+            "#", ".#", "..#", "...#", "....#", ".....#", "......#", ".......#",
+        ],
+    );
+    assert_eq!(chip8.vf(), 0);
+    assert_eq!(chip8.pc(), 0x204);
+}
+
+#[test]
+fn on_cls_runs_once_per_cls_with_the_outgoing_frame() {
+    // I = sprite 0, draw it, then CLS twice
+    let mut chip8 = chip8_with(&[0xF029, 0xD005, 0x00E0, 0x00E0]);
+    let lit = Arc::new(Mutex::new(Vec::new()));
+    let record = Arc::clone(&lit);
+    chip8.set_on_cls(Some(Arc::new(move |display: &[bool; DISPLAY_SIZE]| {
+        let count = display.iter().filter(|&&on| on).count();
+        record.lock().unwrap().push(count);
+    })));
+    run(&mut chip8, 2);
+    assert!(lit.lock().unwrap().is_empty());
+    run(&mut chip8, 2);
+    // The "0" sprite lights 14 pixels; the second CLS sees a blank screen.
+    assert_eq!(*lit.lock().unwrap(), [14, 0]);
+    assert_display(&chip8, &[]);
+}
+
+#[test]
+fn copy_display_into_matches_the_pixels() {
+    // I = sprite 0, draw at (0, 0); V0 = 60, V1 = 30, draw wrapping at (60, 30)
+    let chip8 = run_program(&[0xF029, 0xD005, 0x603C, 0x611E, 0xD015]);
+    let mut out = [false; DISPLAY_SIZE];
+    chip8.copy_display_into(&mut out);
+    for (x, y, on) in chip8.pixels() {
+        assert_eq!(out[y * DISPLAY_WIDTH + x], on, "pixel ({x}, {y})");
+    }
+    assert_eq!(out.iter().filter(|&&on| on).count(), 28);
+}
+
+#[test]
+#[should_panic(expected = "display buffer has the wrong size")]
+fn copy_display_into_rejects_a_short_buffer() {
+    Chip8::with_seed(0).copy_display_into(&mut [false; DISPLAY_SIZE - 1]);
+}
+
+// Pins the picture so the default and bitset-display builds are held to the
+// same output.
+#[test]
+fn wrapped_draws_and_cls_give_the_same_picture_in_every_build() {
+    // V0 = 62, V1 = 30, I = sprite 8, draw wrapping; draw 0 at (0, 0); CLS;
+    // draw 8 again
+    let mut chip8 = chip8_with(&[
+        0x603E, 0x611E, 0x6208, 0xF229, 0xD015, 0x6200, 0xF229, 0xD225, 0x00E0, 0x6208, 0xF229,
+        0xD015,
+    ]);
+    run(&mut chip8, 8);
+    let mut expected = [false; DISPLAY_SIZE];
+    // "8" is F0 90 F0 90 F0, drawn from (62, 30) so it wraps to x = 0..1 and
+    // y = 0..2; "0" is F0 90 90 90 F0 at (0, 0).
+    let eight: [u8; 5] = [0xF0, 0x90, 0xF0, 0x90, 0xF0];
+    let zero: [u8; 5] = [0xF0, 0x90, 0x90, 0x90, 0xF0];
+    for (sprite, (x, y)) in [(eight, (62, 30)), (zero, (0, 0))] {
+        for (row, byte) in sprite.iter().enumerate() {
+            for bit in 0..8 {
+                if byte & (0x80 >> bit) != 0 {
+                    let index =
+                        (x + bit) % DISPLAY_WIDTH + DISPLAY_WIDTH * ((y + row) % DISPLAY_HEIGHT);
+                    expected[index] ^= true;
+                }
+            }
+        }
+    }
+    assert_eq!(chip8.display(), expected);
+    assert_eq!(chip8.vf(), 1);
+
+    run(&mut chip8, 4);
+    let mut copy = [false; DISPLAY_SIZE];
+    chip8.copy_display_into(&mut copy);
+    assert_eq!(chip8.display(), copy);
+    assert_eq!(chip8.display().iter().filter(|&&on| on).count(), 16);
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "ffi")]
+
+use std::ptr;
+
+use chip8::ffi::*;
+use chip8::{Chip8, DISPLAY_SIZE, DISPLAY_WIDTH};
+
+// V0 = 0, I = sprite 0, draw 5 rows at (V0, V0), then the unassigned FFFF
+const DRAW_ZERO: [u8; 8] = [0x60, 0x00, 0xF0, 0x29, 0xD0, 0x05, 0xFF, 0xFF];
+
+#[test]
+fn null_handles_are_ignored() {
+    let null: *mut Chip8 = ptr::null_mut();
+    let mut out = [false; DISPLAY_SIZE];
+    unsafe {
+        assert!(!chip8_load(null, DRAW_ZERO.as_ptr(), DRAW_ZERO.len()));
+        assert!(!chip8_cycle(null));
+        chip8_timer(null);
+        chip8_set_key(null, 0x1, true);
+        assert!(!chip8_copy_display(null, out.as_mut_ptr(), out.len()));
+        assert!(chip8_display(null).is_null());
+        chip8_free(null);
+    }
+}
+
+#[test]
+fn load_rejects_a_null_or_oversized_rom() {
+    let rom = vec![0; Chip8::max_rom_size() as usize + 1];
+    unsafe {
+        let chip8 = chip8_new();
+        assert!(!chip8_load(chip8, ptr::null(), 4));
+        assert!(!chip8_load(chip8, rom.as_ptr(), rom.len()));
+        chip8_free(chip8);
+    }
+}
+
+#[test]
+fn copy_display_checks_the_buffer() {
+    let mut out = [false; DISPLAY_SIZE];
+    unsafe {
+        let chip8 = chip8_new();
+        assert!(!chip8_copy_display(
+            chip8,
+            out.as_mut_ptr(),
+            DISPLAY_SIZE - 1
+        ));
+        assert!(!chip8_copy_display(
+            chip8,
+            out.as_mut_ptr(),
+            DISPLAY_SIZE + 1
+        ));
+        assert!(!chip8_copy_display(chip8, ptr::null_mut(), DISPLAY_SIZE));
+        assert!(chip8_copy_display(chip8, out.as_mut_ptr(), DISPLAY_SIZE));
+        chip8_free(chip8);
+    }
+}
+
+#[test]
+fn load_cycle_and_read_the_display() {
+    let mut out = [false; DISPLAY_SIZE];
+    unsafe {
+        let chip8 = chip8_new();
+        assert_eq!(chip8_display_size(), DISPLAY_SIZE);
+        assert!(chip8_load(chip8, DRAW_ZERO.as_ptr(), DRAW_ZERO.len()));
+        for _ in 0..3 {
+            assert!(chip8_cycle(chip8));
+        }
+        chip8_timer(chip8);
+        assert!(chip8_copy_display(chip8, out.as_mut_ptr(), out.len()));
+        #[cfg(not(feature = "bitset-display"))]
+        assert_eq!(
+            std::slice::from_raw_parts(chip8_display(chip8), DISPLAY_SIZE),
+            out
+        );
+        #[cfg(feature = "bitset-display")]
+        assert!(chip8_display(chip8).is_null());
+        assert!(!chip8_cycle(chip8));
+        chip8_free(chip8);
+    }
+    // The top row of the "0" sprite, then the row below it.
+    assert_eq!(out[..5], [true, true, true, true, false]);
+    assert_eq!(
+        out[DISPLAY_WIDTH..DISPLAY_WIDTH + 5],
+        [true, false, false, true, false]
+    );
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use std::sync::Arc;
+
+use chip8::{keypad_key, Chip8, KeyLayout, KEYMAP};
+use common::{chip8_with, run};
+
+#[test]
+fn keymap_covers_every_keypad_key_once() {
+    let mut targets: Vec<u8> = KEYMAP.iter().map(|&(_, key)| key).collect();
+    targets.sort();
+    assert_eq!(targets, (0x0..=0xF).collect::<Vec<u8>>());
+    assert_eq!(keypad_key('x'), Some(0x0));
+    assert_eq!(keypad_key('P'), None);
+}
+
+#[test]
+fn wait_for_key_callback_supplies_the_key() {
+    // LD V3, K
+    let mut chip8 = chip8_with(&[0xF30A]);
+    chip8.set_wait_for_key(Some(Arc::new(|| Some(0xA))));
+    run(&mut chip8, 1);
+    assert_eq!(chip8.registers()[3], 0xA);
+    assert_eq!(chip8.pc(), 0x202);
+}
+
+#[test]
+fn is_key_pressed_tracks_keys_and_ignores_out_of_range() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.press_key(0x5);
+    assert!(chip8.is_key_pressed(0x5));
+    assert!(!chip8.is_key_pressed(0x6));
+    assert!(!chip8.is_key_pressed(0x10));
+    assert!(!chip8.is_key_pressed(0xFF));
+    chip8.release_key(0x5);
+    assert!(!chip8.is_key_pressed(0x5));
+}
+
+#[test]
+fn release_all_keys_clears_keys_and_the_fx0a_wait() {
+    // LD V3, K
+    let mut chip8 = chip8_with(&[0xF30A]);
+    run(&mut chip8, 1);
+    assert!(chip8.is_waiting_for_key());
+    chip8.press_key(0x1);
+    chip8.press_key(0xE);
+    chip8.release_all_keys();
+    assert_eq!(chip8.pressed_keys().count(), 0);
+    assert!(!chip8.is_waiting_for_key());
+    // Nothing was consumed: Fx0A is still next and waits again.
+    assert_eq!(chip8.pc(), 0x200);
+    run(&mut chip8, 1);
+    assert!(chip8.is_waiting_for_key());
+}
+
+#[test]
+fn fx0a_reports_the_most_recent_press() {
+    // LD V3, K; LD V4, K
+    let mut chip8 = chip8_with(&[0xF30A, 0xF40A]);
+    chip8.press_key(0x2);
+    chip8.press_key(0x5);
+    run(&mut chip8, 1);
+    assert_eq!(chip8.registers()[3], 0x5);
+
+    // Pressing 2 again makes it the latest, over the still-held 5.
+    chip8.release_key(0x2);
+    chip8.press_key(0x2);
+    run(&mut chip8, 1);
+    assert_eq!(chip8.registers()[4], 0x2);
+}
+
+#[test]
+fn every_layout_covers_each_keypad_key_once() {
+    for name in ["classic", "number-row", "azerty"] {
+        let layout = KeyLayout::parse(name).unwrap();
+        let mut targets: Vec<u8> = layout.keymap().iter().map(|&(_, key)| key).collect();
+        targets.sort();
+        assert_eq!(targets, (0x0..=0xF).collect::<Vec<u8>>(), "{name}");
+        for &(c, key) in layout.keymap() {
+            assert_eq!(layout.keypad_key(c), Some(key), "{name}: {c}");
+        }
+    }
+    assert_eq!(KeyLayout::parse("dvorak"), None);
+}
+
+#[test]
+fn fx0a_waits_until_a_key_is_pressed() {
+    // LD V3, K
+    let mut chip8 = chip8_with(&[0xF30A]);
+    assert!(!chip8.is_waiting_for_key());
+    run(&mut chip8, 2);
+    assert!(chip8.is_waiting_for_key());
+    assert_eq!(chip8.pc(), 0x200);
+
+    chip8.press_key(0x7);
+    assert!(!chip8.is_waiting_for_key());
+    run(&mut chip8, 1);
+    assert!(!chip8.is_waiting_for_key());
+    assert_eq!(chip8.registers()[3], 0x7);
+}
+
+#[test]
+fn key_skips_mask_vx_to_the_keypad() {
+    // V0 = 0xFF, SKP V0, ADD V1, 1, SKNP V0, ADD V2, 1
+    let mut chip8 = chip8_with(&[0x60FF, 0xE09E, 0x7101, 0xE0A1, 0x7201]);
+    chip8.press_key(0xF);
+    run(&mut chip8, 4);
+    // 0xFF selects key F: SKP skips, SKNP does not.
+    assert_eq!(chip8.registers()[1], 0);
+    assert_eq!(chip8.registers()[2], 1);
+}
+
+#[test]
+fn ex9e_with_vx_0x1f_reads_key_f() {
+    // V0 = 0x10, ADD V0, 0x0F, SKP V0, ADD V1, 1
+    let program = [0x6010, 0x700F, 0xE09E, 0x7101];
+    let mut chip8 = chip8_with(&program);
+    chip8.press_key(0xF);
+    run(&mut chip8, 4);
+    assert_eq!(chip8.registers()[0], 0x1F);
+    assert_eq!(chip8.pc(), 0x208);
+    assert_eq!(chip8.registers()[1], 0);
+
+    // Key 1, the other nibble, does not count.
+    let mut chip8 = chip8_with(&program);
+    chip8.press_key(0x1);
+    run(&mut chip8, 4);
+    assert_eq!(chip8.registers()[1], 1);
+}
+
+#[test]
+fn just_pressed_fires_for_one_frame() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.press_key(0x3);
+    chip8.press_key(0xC);
+    assert_eq!(chip8.pressed_keys().collect::<Vec<_>>(), [0x3, 0xC]);
+    assert!(chip8.just_pressed(0x3));
+
+    // Held across the next frame, the key is down but no longer an edge.
+    chip8.timer();
+    assert!(chip8.is_key_pressed(0x3));
+    assert!(!chip8.just_pressed(0x3));
+
+    chip8.release_key(0x3);
+    assert!(chip8.just_released(0x3));
+    assert!(!chip8.just_released(0xC));
+    chip8.latch_input();
+    assert!(!chip8.just_released(0x3));
+    assert_eq!(chip8.pressed_keys().collect::<Vec<_>>(), [0xC]);
+    assert!(!chip8.just_pressed(0x10));
+}
@@ -0,0 +1,104 @@
+use chip8::{Chip8, Endianness, Error, Instruction};
+
+#[test]
+fn classic_memory_takes_up_to_3584_bytes() {
+    let mut chip8 = Chip8::with_seed(0);
+    assert_eq!(Chip8::max_rom_size(), 3584);
+    assert!(chip8.load_from_bytes(&[0x12; 3584]).is_ok());
+    assert_eq!(chip8.peek(0xFFF), Some(0x12));
+    assert!(matches!(
+        chip8.load_from_bytes(&[0; 3585]),
+        Err(Error::ROMIsTooBig(3585, 3584))
+    ));
+}
+
+#[test]
+fn load_applies_the_same_limit_to_files() {
+    let path = std::env::temp_dir().join(format!("chip8-too-big-{}.ch8", std::process::id()));
+    std::fs::write(&path, [0; 3585]).unwrap();
+    let result = Chip8::with_seed(0).load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(Error::ROMIsTooBig(3585, 3584))));
+}
+
+// A sparse file one byte past 4 GiB: its length would wrap to 1 if it were
+// cast to a 32-bit usize before being checked. Sparse files are cheap on
+// Unix; elsewhere `set_len` may write out the zeros.
+#[cfg(unix)]
+#[test]
+fn load_checks_sizes_past_u32_max_before_casting() {
+    let path = std::env::temp_dir().join(format!("chip8-huge-{}.ch8", std::process::id()));
+    let file = std::fs::File::create(&path).unwrap();
+    let size = u64::from(u32::MAX) + 2;
+    file.set_len(size).unwrap();
+    drop(file);
+    let mut chip8 = Chip8::with_seed(0);
+    let result = chip8.load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(Error::ROMIsTooBig(s, 3584)) if s == size));
+    assert_eq!(chip8.peek(0x200), Some(0));
+}
+
+#[test]
+fn little_endian_images_are_swapped_back_on_load() {
+    // LD V0, 5; LD I, 0x20A and a trailing odd byte
+    let image = [0x60, 0x05, 0xA2, 0x0A, 0xD0];
+    let swapped = [0x05, 0x60, 0x0A, 0xA2, 0xD0];
+
+    let mut big = Chip8::with_seed(0);
+    big.load_from_bytes(&image).unwrap();
+    let mut little = Chip8::with_seed(0);
+    little.set_rom_endianness(Endianness::Little);
+    little.load_from_bytes(&swapped).unwrap();
+
+    assert_eq!(
+        little.current_instruction(),
+        Some(Instruction::LdByte { x: 0, kk: 5 })
+    );
+    assert_eq!(little.current_instruction(), big.current_instruction());
+    assert_eq!(little.export_memory(), big.export_memory());
+}
+
+#[test]
+fn load_at_places_the_rom_and_pc() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_at(&[0x60, 0x42, 0x12, 0x02], 0x600).unwrap();
+    assert_eq!(chip8.pc(), 0x600);
+    assert_eq!(
+        chip8.memory_slice(0x600, 4),
+        Some(&[0x60, 0x42, 0x12, 0x02][..])
+    );
+    assert_eq!(chip8.peek(0x200), Some(0));
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.registers()[0], 0x42);
+
+    assert!(matches!(
+        chip8.load_at(&[0; 3], 0xFFE),
+        Err(Error::ROMDoesNotFit(3, 0xFFE))
+    ));
+    assert!(matches!(
+        chip8.load_at(&[0], 0x1000),
+        Err(Error::ROMDoesNotFit(1, 0x1000))
+    ));
+}
+
+#[test]
+fn load_default_runs_the_splash_program() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.load_default();
+    assert_eq!(chip8.pc(), 0x200);
+    let rom = include_bytes!("../roms/splash.ch8");
+    assert!(!rom.is_empty());
+    assert_eq!(chip8.memory_slice(0x200, rom.len() as u16), Some(&rom[..]));
+    let splash = chip8.export_memory();
+
+    chip8.fast_forward(10).unwrap();
+    assert!(chip8.pixels().any(|(_, _, on)| on));
+
+    // The splash is stored big-endian whatever the ROM setting.
+    let mut little = Chip8::with_seed(0);
+    little.set_rom_endianness(Endianness::Little);
+    little.load_default();
+    assert_eq!(little.export_memory(), splash);
+    assert_eq!(little.rom_endianness(), Endianness::Little);
+}
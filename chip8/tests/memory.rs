@@ -0,0 +1,230 @@
+mod common;
+
+use chip8::{Chip8, Error, MemoryAccess, MemoryRegion, Quirks};
+use common::{assert_registers, chip8_with, run, run_program};
+
+#[test]
+fn bcd_writes_hundreds_tens_and_ones() {
+    // V0 = 0x9C, I = 0x300, BCD V0
+    let chip8 = run_program(&[0x609C, 0xA300, 0xF033]);
+    assert_eq!(chip8.memory_slice(0x300, 3), Some(&[1, 5, 6][..]));
+}
+
+#[test]
+fn bcd_past_the_end_of_memory_errors() {
+    // I = 0xFFF, BCD V0
+    let mut chip8 = chip8_with(&[0xAFFF, 0xF033]);
+    run(&mut chip8, 1);
+    assert!(matches!(chip8.cycle(), Err(Error::AddressOutOfBounds(_))));
+}
+
+// I = 0xFFF, V0 = 0xFF, I += V0 leaves I at 0x10FE, past the end of memory.
+const I_PAST_THE_END: [u16; 3] = [0xAFFF, 0x60FF, 0xF01E];
+
+fn with_i_past_the_end(opcodes: &[u16]) -> Chip8 {
+    let program: Vec<u16> = I_PAST_THE_END.iter().chain(opcodes).copied().collect();
+    let mut chip8 = chip8_with(&program);
+    run(&mut chip8, I_PAST_THE_END.len());
+    chip8
+}
+
+#[test]
+fn i_past_the_end_errors_in_strict_mode() {
+    for opcode in [0xF033, 0xF055, 0xF065] {
+        let mut chip8 = with_i_past_the_end(&[opcode]);
+        assert!(
+            matches!(chip8.cycle(), Err(Error::AddressOutOfBounds(0x10FE))),
+            "{opcode:04X}"
+        );
+    }
+
+    // Dxyn has nothing to read and draws nothing.
+    let mut chip8 = with_i_past_the_end(&[0xD011]);
+    assert!(!chip8.cycle().unwrap().display_changed);
+    assert_eq!(chip8.pc(), 0x208);
+}
+
+#[test]
+fn i_past_the_end_wraps_in_lenient_mode() {
+    // BCD V0 at 0x0FE, then read it back into V0 and V1
+    let mut chip8 = with_i_past_the_end(&[0xF033, 0xF165]);
+    chip8.set_memory_access(MemoryAccess::Lenient);
+    run(&mut chip8, 2);
+    assert_eq!(chip8.memory_slice(0x0FE, 3), Some(&[2, 5, 5][..]));
+    assert_registers(&chip8, &[(0, 2), (1, 5)]);
+}
+
+#[test]
+fn memory_quirk_increment_past_the_end() {
+    // I = 0xFFF, store V0, store V0 again at I = 0x1000
+    let mut chip8 = chip8_with(&[0xAFFF, 0xF055, 0xF055]);
+    chip8.set_quirks(Quirks {
+        memory: true,
+        ..Quirks::default()
+    });
+    run(&mut chip8, 2);
+    assert!(matches!(
+        chip8.cycle(),
+        Err(Error::AddressOutOfBounds(0x1000))
+    ));
+
+    // Lenient mode wraps the second load to the first font byte.
+    let mut chip8 = chip8_with(&[0xAFFF, 0xF055, 0xF065, 0xD011]);
+    chip8.set_quirks(Quirks {
+        memory: true,
+        ..Quirks::default()
+    });
+    chip8.set_memory_access(MemoryAccess::Lenient);
+    run(&mut chip8, 4);
+    assert_registers(&chip8, &[(0, 0xF0)]);
+}
+
+#[test]
+fn load_all_registers_near_the_end_of_memory() {
+    // I = 0xFF5, LD VF, [I]: reads 0xFF5..=0x1004
+    let program = [0xAFF5, 0xFF65];
+    let data: Vec<u8> = (1..=11).collect();
+
+    let mut chip8 = chip8_with(&program);
+    chip8.write_rom_region(&data, 0xFF5).unwrap();
+    run(&mut chip8, 1);
+    assert!(matches!(
+        chip8.cycle(),
+        Err(Error::AddressOutOfBounds(0xFF5))
+    ));
+    assert_eq!(*chip8.registers(), [0; 16]);
+
+    // Lenient mode carries on from 0x000, the start of the "0" font sprite.
+    let mut chip8 = chip8_with(&program);
+    chip8.write_rom_region(&data, 0xFF5).unwrap();
+    chip8.set_memory_access(MemoryAccess::Lenient);
+    run(&mut chip8, 2);
+    assert_eq!(chip8.registers()[..11], data[..]);
+    assert_eq!(chip8.registers()[11..], [0xF0, 0x90, 0x90, 0x90, 0xF0]);
+}
+
+#[test]
+fn writes_into_executed_code_are_flagged() {
+    // I = 0x200, V0 = 0x12, LD [I], V0; JP 0x206
+    let mut chip8 = chip8_with(&[0xA200, 0x6012, 0xF055, 0x1206]);
+    chip8.set_self_modifying_log(true);
+    run(&mut chip8, 3);
+    assert_eq!(chip8.self_modifying_writes(), [0x200]);
+
+    // A new program starts with a clean log and code region: 0x206 has not
+    // run yet when it is written.
+    // I = 0x206, LD [I], V0; JP 0x204
+    chip8
+        .load_from_bytes(&[0xA2, 0x06, 0xF0, 0x55, 0x12, 0x04])
+        .unwrap();
+    assert!(chip8.self_modifying_writes().is_empty());
+    run(&mut chip8, 2);
+    assert!(chip8.self_modifying_writes().is_empty());
+}
+
+#[test]
+fn memory_regions_cover_the_address_space() {
+    let regions = Chip8::with_seed(0).memory_regions();
+    assert_eq!(
+        regions,
+        [
+            MemoryRegion {
+                name: "font",
+                start: 0x000,
+                end: 0x04F,
+            },
+            MemoryRegion {
+                name: "reserved",
+                start: 0x050,
+                end: 0x1FF,
+            },
+            MemoryRegion {
+                name: "program",
+                start: 0x200,
+                end: 0xFFF,
+            },
+        ]
+    );
+}
+
+#[rustfmt::skip]
+const FONT: [u8; 80] = [
+    /*0*/ 0xF0, 0x90, 0x90, 0x90, 0xF0, /*1*/ 0x20, 0x60, 0x20, 0x20, 0x70,
+    /*2*/ 0xF0, 0x10, 0xF0, 0x80, 0xF0, /*3*/ 0xF0, 0x10, 0xF0, 0x10, 0xF0,
+    /*4*/ 0x90, 0x90, 0xF0, 0x10, 0x10, /*5*/ 0xF0, 0x80, 0xF0, 0x10, 0xF0,
+    /*6*/ 0xF0, 0x80, 0xF0, 0x90, 0xF0, /*7*/ 0xF0, 0x10, 0x20, 0x40, 0x40,
+    /*8*/ 0xF0, 0x90, 0xF0, 0x90, 0xF0, /*9*/ 0xF0, 0x90, 0xF0, 0x10, 0xF0,
+    /*A*/ 0xF0, 0x90, 0xF0, 0x90, 0x90, /*B*/ 0xE0, 0x90, 0xE0, 0x90, 0xE0,
+    /*C*/ 0xF0, 0x80, 0x80, 0x80, 0xF0, /*D*/ 0xE0, 0x90, 0x90, 0x90, 0xE0,
+    /*E*/ 0xF0, 0x80, 0xF0, 0x80, 0xF0, /*F*/ 0xF0, 0x80, 0xF0, 0x80, 0x80,
+];
+
+#[test]
+fn memory_slice_reads_the_font() {
+    let chip8 = Chip8::with_seed(0);
+    assert_eq!(chip8.memory_slice(0x000, 80), Some(&FONT[..]));
+    // The "F" sprite alone, and an empty range at the very end.
+    assert_eq!(chip8.memory_slice(75, 5), Some(&FONT[75..]));
+    assert_eq!(chip8.memory_slice(0x1000, 0), Some(&[][..]));
+}
+
+#[test]
+fn memory_slice_past_the_end_is_none() {
+    let chip8 = Chip8::with_seed(0);
+    assert_eq!(chip8.memory_slice(0xFFF, 2), None);
+    assert_eq!(chip8.memory_slice(0x1000, 1), None);
+    assert_eq!(chip8.memory_slice(0x000, 0x1001), None);
+    assert_eq!(chip8.memory_slice(0xFFF, 1).map(<[u8]>::len), Some(1));
+}
+
+#[test]
+fn write_rom_region_patches_memory_without_moving_pc() {
+    // LD V0, 1; LD V1, 2
+    let mut chip8 = chip8_with(&[0x6001, 0x6102]);
+    run(&mut chip8, 1);
+    chip8
+        .write_rom_region(&[0xDE, 0xAD, 0xBE, 0xEF], 0x400)
+        .unwrap();
+    assert_eq!(chip8.peek(0x3FF), Some(0));
+    assert_eq!(
+        chip8.memory_slice(0x400, 4),
+        Some(&[0xDE, 0xAD, 0xBE, 0xEF][..])
+    );
+    assert_eq!(chip8.peek(0x404), Some(0));
+    assert_eq!(chip8.pc(), 0x202);
+    run(&mut chip8, 1);
+    assert_registers(&chip8, &[(0, 1), (1, 2)]);
+}
+
+#[test]
+fn write_rom_region_rejects_the_interpreter_area_and_overruns() {
+    let mut chip8 = Chip8::with_seed(0);
+    assert!(matches!(
+        chip8.write_rom_region(&[0xFF], 0x1FF),
+        Err(Error::AddressOutOfBounds(0x1FF))
+    ));
+    assert!(matches!(
+        chip8.write_rom_region(&[0xFF; 2], 0xFFF),
+        Err(Error::ROMDoesNotFit(2, 0xFFF))
+    ));
+    assert_eq!(chip8.peek(0x1FF), Some(0));
+    assert_eq!(chip8.peek(0xFFF), Some(0));
+    chip8.write_rom_region(&[0xFF], 0xFFF).unwrap();
+    assert_eq!(chip8.peek(0xFFF), Some(0xFF));
+}
+
+#[test]
+fn annn_reaches_the_whole_address_space() {
+    // LD V0, 0x42; LD I, 0x800; LD [I], V0; LD I, 0xFFF; LD [I], V0
+    let chip8 = run_program(&[0x6042, 0xA800, 0xF055, 0xAFFF, 0xF055]);
+    assert_eq!(chip8.peek(0x800), Some(0x42));
+    assert_eq!(chip8.peek(0xFFF), Some(0x42));
+}
+
+#[test]
+fn f000_is_not_a_long_load() {
+    // There is no extended memory, so XO-CHIP's F000 NNNN is unassigned
+    // rather than an I load that reaches past 0xFFF.
+    let mut chip8 = chip8_with(&[0xF000, 0x8000]);
+    assert!(matches!(chip8.cycle(), Err(Error::UnknownOpcode(0xF000))));
+}
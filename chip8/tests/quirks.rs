@@ -0,0 +1,17 @@
+use chip8::Quirks;
+
+#[test]
+fn octo_preset_matches_octo_defaults() {
+    let octo = Quirks::octo();
+    // Load and store leave I alone.
+    assert!(!octo.memory);
+    // 8xy6 and 8xyE shift Vx in place.
+    assert!(octo.shifting);
+    // Sprites are clipped at the edges.
+    assert!(octo.clipping);
+    // Logic ops leave VF alone.
+    assert!(!octo.vf_reset);
+    // Bnnn jumps relative to V0.
+    assert!(!octo.jumping);
+    assert!(!octo.display_wait);
+}
@@ -0,0 +1,15 @@
+mod common;
+
+use chip8::Chip8;
+use common::{run, SequenceRng};
+
+#[test]
+fn rnd_masks_the_scripted_byte() {
+    // RND V0, 0x0F; RND V1, 0xFF; RND V2, 0xF0
+    let mut chip8 = Chip8::with_rng(Box::new(SequenceRng::new(&[0xFF, 0x5A])));
+    chip8
+        .load_from_bytes(&[0xC0, 0x0F, 0xC1, 0xFF, 0xC2, 0xF0])
+        .unwrap();
+    run(&mut chip8, 3);
+    assert_eq!(&chip8.registers()[..3], &[0x0F, 0x5A, 0xF0]);
+}
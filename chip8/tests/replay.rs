@@ -0,0 +1,63 @@
+mod common;
+
+use chip8::Chip8;
+use common::chip8_with;
+
+// LD V1, K; ADD V0, V1; JP 0x200
+const SUM_KEYS: &[u16] = &[0xF10A, 0x8014, 0x1200];
+
+fn press_and_release(chip8: &mut Chip8, key: u8) {
+    chip8.press_key(key);
+    chip8.step_frame().unwrap();
+    chip8.release_key(key);
+    chip8.step_frame().unwrap();
+}
+
+#[test]
+fn replaying_a_recorded_trace_reaches_the_same_state() {
+    let mut live = chip8_with(SUM_KEYS);
+    live.set_recording(true);
+    live.step_frame().unwrap();
+    press_and_release(&mut live, 0x3);
+    press_and_release(&mut live, 0x7);
+    live.step_frame().unwrap();
+    assert_eq!(live.input_trace().len(), 4);
+
+    let mut replayed = chip8_with(SUM_KEYS);
+    replayed.replay_input(live.input_trace());
+    while replayed.frame_count() < live.frame_count() {
+        replayed.step_frame().unwrap();
+    }
+    assert_ne!(live.registers()[0], 0);
+    assert_eq!(replayed.registers(), live.registers());
+    assert_eq!(replayed.pc(), live.pc());
+    assert_eq!(replayed.snapshot(), live.snapshot());
+}
+
+// LD V1, K; RND V0, 0x3F; RND V2, 0x1F; LD F, V1; DRW V0, V2, 5; JP 0x200
+const DRAW_KEYS: &[u16] = &[0xF10A, 0xC03F, 0xC21F, 0xF129, 0xD025, 0x1200];
+
+fn framebuffer(chip8: &Chip8) -> Vec<bool> {
+    chip8.pixels().map(|(_, _, on)| on).collect()
+}
+
+#[test]
+fn rewinding_reproduces_the_live_framebuffer() {
+    let mut live = chip8_with(DRAW_KEYS);
+    live.set_recording(true);
+    live.step_frame().unwrap();
+    press_and_release(&mut live, 0x3);
+    let frame = live.frame_count();
+    let expected = framebuffer(&live);
+    let registers = *live.registers();
+
+    press_and_release(&mut live, 0x9);
+    live.step_frame().unwrap();
+    assert_ne!(framebuffer(&live), expected);
+
+    live.rewind_to_frame(frame).unwrap();
+    assert_eq!(live.frame_count(), frame);
+    assert!(expected.contains(&true));
+    assert_eq!(framebuffer(&live), expected);
+    assert_eq!(*live.registers(), registers);
+}
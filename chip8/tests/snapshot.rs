@@ -0,0 +1,144 @@
+mod common;
+
+use chip8::{Chip8, Quirks};
+use common::{chip8_with, run, SequenceRng};
+
+#[test]
+fn restore_returns_to_the_snapshot() {
+    // V0 = 1, I = sprite 1, draw; then V0 = 8, I = sprite 8, draw at (8, 8)
+    let mut chip8 = chip8_with(&[0x6001, 0xF029, 0xD005, 0x6008, 0xF029, 0xD005]);
+    run(&mut chip8, 3);
+    let snapshot = chip8.snapshot();
+    let pixels: Vec<_> = chip8.pixels().collect();
+    let registers = *chip8.registers();
+
+    run(&mut chip8, 3);
+    assert_ne!(chip8.pixels().collect::<Vec<_>>(), pixels);
+    assert_ne!(*chip8.registers(), registers);
+
+    chip8.restore(&snapshot);
+    assert_eq!(chip8.pixels().collect::<Vec<_>>(), pixels);
+    assert_eq!(*chip8.registers(), registers);
+    assert_eq!(chip8.pc(), 0x206);
+    assert_eq!(chip8.snapshot(), snapshot);
+}
+
+#[test]
+fn clones_run_independently_and_compare_by_state() {
+    // RND V0, 0xFF; JP 0x200
+    let program = [0xC0FF, 0x1200];
+    let original = chip8_with(&program);
+    assert_eq!(chip8_with(&program), original);
+
+    let mut clone = original.clone();
+    run(&mut clone, 1);
+    assert_ne!(clone, original);
+    assert_eq!(original.pc(), 0x200);
+    assert_eq!(original.registers()[0], 0);
+
+    // A machine that has halted behaves differently from one that has not.
+    let mut halted = original.clone();
+    halted.halt();
+    assert_ne!(halted, original);
+}
+
+#[test]
+fn restore_round_trips_to_an_equal_machine() {
+    // ADD V0, 1; LD I, 0x300; LD [I], V0; JP 0x200
+    let mut chip8 = chip8_with(&[0x7001, 0xA300, 0xF055, 0x1200]);
+    run(&mut chip8, 2);
+    let before = chip8.clone();
+    let snapshot = chip8.snapshot();
+
+    run(&mut chip8, 6);
+    assert_ne!(chip8, before);
+    chip8.restore(&snapshot);
+    assert_eq!(chip8, before);
+}
+
+#[test]
+fn memory_image_round_trips_through_export() {
+    let mut image = [0; 0x1000];
+    image[0x000] = 0xAB;
+    image[0x200] = 0x12;
+    image[0xFFF] = 0xCD;
+    let chip8 = Chip8::from_memory_image(&image, 0x2A4);
+    assert_eq!(chip8.export_memory(), image);
+    assert_eq!(chip8.pc(), 0x2A4);
+}
+
+#[test]
+fn imported_dump_continues_like_the_original() {
+    // The tail reloads V0 and V1 from memory, so only the dump and pc are
+    // needed to carry on.
+    let program = [
+        0x6005, // LD V0, 5
+        0x6107, // LD V1, 7
+        0xA300, // LD I, 0x300
+        0xF155, // LD [I], V1
+        0xA300, // LD I, 0x300
+        0xF165, // LD V1, [I]
+        0x8014, // ADD V0, V1
+        0xF029, // LD F, V0
+        0xD015, // DRW V0, V1, 5
+    ];
+    let mut original = chip8_with(&program);
+    run(&mut original, 4);
+    let mut imported = Chip8::from_memory_image(&original.export_memory(), original.pc());
+
+    run(&mut original, 5);
+    run(&mut imported, 5);
+    assert_eq!(imported.registers()[0], 12);
+    assert_eq!(imported, original);
+}
+
+#[test]
+fn export_then_import_gives_an_equal_machine() {
+    // LD V0, 0x2A; LD I, 0x300; LD [I], V0
+    let mut chip8 = chip8_with(&[0x602A, 0xA300, 0xF055]);
+    run(&mut chip8, 3);
+    let mut export = chip8.export_memory();
+    let imported = Chip8::from_memory_image(&export, 0x200);
+    assert_eq!(imported.export_memory(), chip8.export_memory());
+    assert_eq!(imported.peek(0x300), Some(0x2A));
+
+    // The export is a copy.
+    export[0x300] = 0;
+    assert_ne!(export, chip8.export_memory());
+    assert_eq!(chip8.peek(0x300), Some(0x2A));
+    assert_eq!(imported.peek(0x300), Some(0x2A));
+}
+
+#[test]
+fn imported_dump_starts_with_default_quirks_and_takes_an_rng() {
+    // RND V0, 0xFF; RND V1, 0x0F
+    let image = chip8_with(&[0xC0FF, 0xC10F]).export_memory();
+    let mut chip8 = Chip8::from_memory_image(&image, 0x200);
+    assert_eq!(chip8.quirks(), Quirks::default());
+    assert_eq!(*chip8.registers(), [0; 16]);
+
+    chip8.set_rng(Box::new(SequenceRng::new(&[0xA5, 0x3C])));
+    run(&mut chip8, 2);
+    assert_eq!(chip8.registers()[0], 0xA5);
+    assert_eq!(chip8.registers()[1], 0x0C);
+}
+
+#[test]
+fn debug_output_summarizes_the_registers() {
+    // LD V1, 0xAB; LD I, 0x2F0
+    let mut chip8 = chip8_with(&[0x61AB, 0xA2F0]);
+    run(&mut chip8, 2);
+    let debug = format!("{chip8:?}");
+    assert!(
+        debug.starts_with("Chip8 { pc: 0x204, I: 0x2F0, sp: 0, "),
+        "{debug}"
+    );
+    assert!(debug.contains("V: [00, AB, 00,"), "{debug}");
+    assert!(debug.ends_with(", .. }"), "{debug}");
+
+    // Equal memory hashes the same; a changed byte shows up.
+    let same = format!("{:?}", chip8.clone());
+    assert_eq!(same, debug);
+    chip8.write_rom_region(&[0xFF], 0x300).unwrap();
+    assert_ne!(format!("{chip8:?}"), debug);
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::{chip8_with, run, run_program};
+
+#[test]
+fn opcode_stats_count_families_and_sub_buckets() {
+    // LD V0, 1; ADD V0, V1; LD [I], V0
+    let stats = run_program(&[0x6001, 0x8014, 0xF055]).opcode_stats();
+    assert_eq!(stats.total(), 3);
+    assert_eq!(stats.families[0x6], 1);
+    assert_eq!(stats.families[0x8], 1);
+    assert_eq!(stats.families[0xF], 1);
+    assert_eq!(stats.alu[0x4], 1);
+    assert_eq!(stats.misc[0x55], 1);
+}
+
+#[test]
+fn a_key_wait_counts_once() {
+    // LD V0, K
+    let mut chip8 = chip8_with(&[0xF00A]);
+    for _ in 0..5 {
+        chip8.step_frame().unwrap();
+    }
+    assert!(chip8.is_waiting_for_key());
+    assert_eq!(chip8.opcode_stats().total(), 0);
+
+    chip8.press_key(0x4);
+    run(&mut chip8, 1);
+    let stats = chip8.opcode_stats();
+    assert_eq!(stats.total(), 1);
+    assert_eq!(stats.misc[0x0A], 1);
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use std::time::Duration;
+
+use chip8::{Chip8, Quirks, Waveform};
+use common::{chip8_with, run};
+
+#[test]
+fn cycles_per_frame_follows_the_clock() {
+    let mut chip8 = Chip8::with_seed(0);
+    assert_eq!(chip8.clock_hz(), 800);
+    assert_eq!(chip8.cycles_per_frame(), 13);
+    chip8.set_clock_hz(600);
+    assert_eq!(chip8.cycles_per_frame(), 10);
+}
+
+#[test]
+fn tick_timers_counts_whole_sixtieths_and_keeps_the_rest() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_delay_timer(10);
+    chip8.tick_timers(Duration::from_millis(50));
+    assert_eq!(chip8.delay_timer(), 7);
+    // 10ms alone is not a tick, but it carries over to the next call.
+    chip8.tick_timers(Duration::from_millis(10));
+    assert_eq!(chip8.delay_timer(), 7);
+    chip8.tick_timers(Duration::from_millis(7));
+    assert_eq!(chip8.delay_timer(), 6);
+}
+
+#[test]
+fn sound_timer_beeps_until_it_runs_out() {
+    let mut chip8 = Chip8::with_seed(0);
+    assert!(!chip8.is_beeping());
+    chip8.set_sound_timer(10);
+    for remaining in (1..=10).rev() {
+        assert_eq!(chip8.sound_timer(), remaining);
+        assert!(chip8.is_beeping());
+        chip8.timer();
+    }
+    assert_eq!(chip8.sound_timer(), 0);
+    assert!(!chip8.is_beeping());
+
+    chip8.set_delay_timer(3);
+    chip8.timer();
+    assert_eq!(chip8.delay_timer(), 2);
+}
+
+#[test]
+fn take_new_frame_reports_each_tick_once() {
+    let mut chip8 = Chip8::with_seed(0);
+    assert!(!chip8.take_new_frame());
+    chip8.timer();
+    assert!(chip8.take_new_frame());
+    assert!(!chip8.take_new_frame());
+    chip8.timer();
+    chip8.timer();
+    assert!(chip8.take_new_frame());
+    assert!(!chip8.take_new_frame());
+}
+
+#[test]
+fn fx07_reads_only_whole_ticks() {
+    // LD V0, 5; LD DT, V0; LD V1, DT; LD V2, DT
+    let mut chip8 = chip8_with(&[0x6005, 0xF015, 0xF107, 0xF207]);
+    run(&mut chip8, 2);
+    chip8.tick_timers(Duration::from_millis(16));
+    run(&mut chip8, 1);
+    assert_eq!(chip8.registers()[1], 5);
+    // The leftover 16ms plus 1ms completes the first 1/60s.
+    chip8.tick_timers(Duration::from_millis(1));
+    run(&mut chip8, 1);
+    assert_eq!(chip8.registers()[2], 4);
+}
+
+#[test]
+fn sine_beep_stays_within_full_scale() {
+    let mut chip8 = Chip8::with_seed(0);
+    chip8.set_waveform(Waveform::Sine);
+    chip8.set_sound_timer(2);
+    let mut out = [2.0; 1000];
+    chip8.audio_samples(&mut out, 48000);
+    assert!(out.iter().all(|s| (-1.0..=1.0).contains(s)), "{out:?}");
+    // Two ticks at 48kHz is 1600 samples, more than the buffer holds.
+    assert!(out.iter().any(|&s| s > 0.9));
+    assert!(out.iter().any(|&s| s < -0.9));
+}
+
+#[test]
+fn square_beep_takes_two_values_then_falls_silent() {
+    let mut chip8 = Chip8::with_seed(0);
+    assert_eq!(chip8.waveform(), Waveform::Square);
+    chip8.set_sound_timer(1);
+    // One tick at 48kHz is 800 samples.
+    let mut out = [2.0; 1000];
+    chip8.audio_samples(&mut out, 48000);
+    assert!(out[..800].iter().all(|&s| s == 1.0 || s == -1.0));
+    assert!(out[..800].contains(&1.0) && out[..800].contains(&-1.0));
+    assert!(out[800..].iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn fx18_sets_the_frames_left_to_beep() {
+    // LD V3, 20; LD ST, V3
+    let mut chip8 = chip8_with(&[0x6314, 0xF318]);
+    run(&mut chip8, 2);
+    assert_eq!(chip8.sound_frames_remaining(), 20);
+    chip8.timer();
+    assert_eq!(chip8.sound_frames_remaining(), 19);
+    for _ in 0..19 {
+        chip8.timer();
+    }
+    assert_eq!(chip8.sound_frames_remaining(), 0);
+    assert!(!chip8.is_beeping());
+}
+
+#[test]
+fn run_to_vblank_runs_one_frame_of_a_loop() {
+    // ADD V0, 1; JP 0x200
+    let mut chip8 = chip8_with(&[0x7001, 0x1200]);
+    assert_eq!(chip8.run_to_vblank().unwrap(), 13);
+    assert_eq!(chip8.registers()[0], 7);
+    assert_eq!(chip8.frame_count(), 1);
+    assert_eq!(chip8.pc(), 0x202);
+}
+
+#[test]
+fn run_to_vblank_stops_at_a_held_draw() {
+    // ADD V0, 1; DRW V1, V1, 1; JP 0x200
+    let mut chip8 = chip8_with(&[0x7001, 0xD111, 0x1200]);
+    chip8.set_quirks(Quirks {
+        display_wait: true,
+        ..Quirks::default()
+    });
+    // The draw waits for the first tick, then runs at the start of the next
+    // frame and holds the loop again one lap later.
+    assert_eq!(chip8.run_to_vblank().unwrap(), 1);
+    assert_eq!(chip8.pc(), 0x202);
+    assert_eq!(chip8.run_to_vblank().unwrap(), 3);
+    assert_eq!(chip8.registers()[0], 2);
+    assert_eq!(chip8.frame_count(), 2);
+}
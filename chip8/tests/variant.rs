@@ -0,0 +1,40 @@
+use chip8::{detect_variant, Variant};
+
+fn image(opcodes: &[u16]) -> Vec<u8> {
+    opcodes.iter().flat_map(|op| op.to_be_bytes()).collect()
+}
+
+#[test]
+fn plain_programs_are_chip8() {
+    assert_eq!(
+        Variant::from_rom(&image(&[0x00E0, 0x6001, 0x1202])),
+        Variant::Chip8
+    );
+}
+
+#[test]
+fn xo_chip_only_opcodes_are_detected() {
+    // 00D1 scroll up, 5122 save range, F000 long load, F201 plane, F002 audio
+    for opcode in [0x00D1, 0x5122, 0xF000, 0xF201, 0xF002] {
+        let rom = image(&[0x00E0, opcode, 0x1202]);
+        assert_eq!(Variant::from_rom(&rom), Variant::XoChip, "{opcode:04X}");
+    }
+}
+
+#[test]
+fn xo_chip_wins_over_super_chip() {
+    assert_eq!(
+        Variant::from_rom(&image(&[0x00FF, 0xF230])),
+        Variant::SuperChip
+    );
+    assert_eq!(
+        Variant::from_rom(&image(&[0x00FF, 0x5123])),
+        Variant::XoChip
+    );
+}
+
+#[test]
+fn extension_decides_without_reading_the_file() {
+    assert_eq!(detect_variant("missing.xo8").unwrap(), Variant::XoChip);
+    assert_eq!(detect_variant("missing.SC8").unwrap(), Variant::SuperChip);
+}
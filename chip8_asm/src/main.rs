@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+// Usage: chip8_asm <source> [-o <rom>]
+// The ROM defaults to the source path with a .ch8 extension.
+fn main() -> ExitCode {
+    let mut source = None;
+    let mut out = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" => out = Some(PathBuf::from(args.next().expect("-o expects a file path."))),
+            _ => source = Some(PathBuf::from(arg)),
+        }
+    }
+
+    let source = source.expect("No source path is provided.");
+    let out = out.unwrap_or_else(|| source.with_extension("ch8"));
+
+    let result = std::fs::read_to_string(&source)
+        .map_err(chip8::Error::from)
+        .and_then(|text| chip8::asm::assemble(&text))
+        .and_then(|rom| std::fs::write(&out, rom).map_err(chip8::Error::from));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}: {e}", source.display());
+            ExitCode::FAILURE
+        }
+    }
+}
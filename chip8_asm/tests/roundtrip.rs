@@ -0,0 +1,26 @@
+use chip8::asm::assemble;
+
+const SOURCE: &str = "\
+LD V0, 0x05
+LD I, 0x20A
+DRW V0, V1, 5
+JP 0x206
+";
+
+#[test]
+fn disassembly_reproduces_the_source() {
+    let rom = assemble(SOURCE).unwrap();
+    let listing = chip8::disassemble(&rom, 0x200);
+    // Each listing line is `ADDR  OPCODE  MNEMONIC`.
+    let mnemonics: Vec<&str> = listing
+        .lines()
+        .map(|line| line.splitn(3, "  ").nth(2).unwrap())
+        .collect();
+    assert_eq!(mnemonics, SOURCE.lines().collect::<Vec<_>>());
+}
+
+#[test]
+fn labels_resolve_to_their_address() {
+    let rom = assemble("start: CLS\nloop: JP loop\nCALL start").unwrap();
+    assert_eq!(rom, [0x00, 0xE0, 0x12, 0x02, 0x22, 0x00]);
+}
@@ -1,14 +1,16 @@
 use std::ffi::CString;
 use std::sync::mpsc::Receiver;
 use std::thread::sleep;
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 use glfw::{
     Action, Context, Glfw, Key, OpenGlProfileHint, Window, WindowEvent, WindowHint, WindowMode,
 };
 
-use chip8::Chip8;
+use chip8::{Chip8, KeyLayout};
+use chip8_util::{flash_colors, rgba, FramePacer, GameSettings, OpcodeTrace};
 
+use crate::args::Args;
 use crate::gl;
 use crate::gl::types::*;
 
@@ -16,24 +18,35 @@ const WIDTH: u32 = 800;
 const HEIGHT: u32 = 600;
 const TITLE: &'static str = "chip8_rs";
 
-const CHIP8_FREQ: f32 = 800.0;
-const TIMER_FREQ: f32 = 60.0;
+const TIMER_FREQ: u32 = 60;
+const TURBO_MULTIPLIER: u32 = 10;
+
+const PAUSE_KEY: Key = Key::F5;
+const STEP_KEY: Key = Key::F10;
 
 pub struct App {
+    // Declared first so the GL objects are deleted while the window's
+    // context is still alive.
+    gl_context: GlContext,
     window: Window,
     events: Receiver<(f64, WindowEvent)>,
     glfw: Glfw,
     chip8: Chip8,
     pixels: [u32; chip8::DISPLAY_WIDTH * chip8::DISPLAY_HEIGHT],
-    gl_context: GlContext,
-    _start_time: Instant,
-    cpu_timer: Instant,
-    timer: Instant,
+    pacer: FramePacer,
     frame_count: u64,
+    fg_color: u32,
+    bg_color: u32,
+    flash: bool,
+    turbo: bool,
+    paused: bool,
+    step: bool,
+    layout: KeyLayout,
+    trace: Option<OpcodeTrace>,
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(args: &Args, settings: &GameSettings) -> Self {
         let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).expect("Failed to init GLFW.");
 
         glfw.window_hint(WindowHint::ContextVersion(3, 3));
@@ -45,6 +58,7 @@ impl App {
             .expect("Failed to create GLFW window.");
 
         window.set_key_polling(true);
+        window.set_focus_polling(true);
         window.set_framebuffer_size_polling(true);
         window.make_current();
 
@@ -52,31 +66,50 @@ impl App {
 
         gl::load_with(|s| glfw.get_proc_address_raw(s));
 
-        let mut vao = 0;
-        let mut vbo = 0;
-
-        unsafe {
-            gl::GenVertexArrays(1, &mut vao);
-            gl::GenBuffers(1, &mut vbo);
+        let mut chip8 = Chip8::new();
+        chip8.set_clock_hz(settings.clock_hz);
+        chip8.set_quirks(settings.quirks.quirks());
+        let trace = args
+            .log_opcodes
+            .as_ref()
+            .and_then(|path| match OpcodeTrace::create(path) {
+                Ok(trace) => Some(trace),
+                Err(e) => {
+                    eprintln!("Could not open {path}: {e}");
+                    None
+                }
+            });
+        if let Some(trace) = &trace {
+            trace.install(&mut chip8);
         }
+        let pacer = FramePacer::new(chip8.clock_hz(), TIMER_FREQ, Instant::now());
 
         App {
             window,
             events,
             glfw,
-            chip8: Chip8::new(),
+            chip8,
             pixels: [0; chip8::DISPLAY_WIDTH * chip8::DISPLAY_HEIGHT],
             gl_context: GlContext::new(),
-            _start_time: Instant::now(),
-            cpu_timer: Instant::now(),
-            timer: Instant::now(),
+            pacer,
             frame_count: 0,
+            fg_color: settings.fg_color,
+            bg_color: settings.bg_color,
+            flash: args.flash,
+            turbo: false,
+            paused: args.paused,
+            step: false,
+            layout: args.layout,
+            trace,
         }
     }
 
-    pub fn run(&mut self) {
-        let path = std::env::args().nth(1).expect("No ROM path is provided.");
-        self.chip8.load(path).unwrap();
+    pub fn run(&mut self, rom: Option<&[u8]>) -> chip8::Result<()> {
+        match rom {
+            Some(rom) => self.chip8.load_from_bytes(rom)?,
+            None => self.chip8.load_default(),
+        }
+        self.present();
         while !self.window.should_close() {
             let current_time = Instant::now();
 
@@ -90,69 +123,88 @@ impl App {
                     WindowEvent::FramebufferSize(width, height) => unsafe {
                         gl::Viewport(0, 0, width, height);
                     },
-                    WindowEvent::Key(key, _, Action::Press, _) => match key {
-                        Key::Kp1 => self.chip8.keys[0x1] = true,
-                        Key::Kp2 => self.chip8.keys[0x2] = true,
-                        Key::Kp3 => self.chip8.keys[0x3] = true,
-                        Key::Kp4 => self.chip8.keys[0xC] = true,
-                        Key::Q => self.chip8.keys[0x4] = true,
-                        Key::W => self.chip8.keys[0x5] = true,
-                        Key::E => self.chip8.keys[0x6] = true,
-                        Key::R => self.chip8.keys[0xD] = true,
-                        Key::A => self.chip8.keys[0x7] = true,
-                        Key::S => self.chip8.keys[0x8] = true,
-                        Key::D => self.chip8.keys[0x9] = true,
-                        Key::F => self.chip8.keys[0xE] = true,
-                        Key::Z => self.chip8.keys[0xA] = true,
-                        Key::X => self.chip8.keys[0x0] = true,
-                        Key::C => self.chip8.keys[0xB] = true,
-                        Key::V => self.chip8.keys[0xF] = true,
-                        _ => {}
-                    },
-                    WindowEvent::Key(key, _, Action::Release, _) => match key {
-                        Key::Kp1 => self.chip8.keys[0x1] = false,
-                        Key::Kp2 => self.chip8.keys[0x2] = false,
-                        Key::Kp3 => self.chip8.keys[0x3] = false,
-                        Key::Kp4 => self.chip8.keys[0xC] = false,
-                        Key::Q => self.chip8.keys[0x4] = false,
-                        Key::W => self.chip8.keys[0x5] = false,
-                        Key::E => self.chip8.keys[0x6] = false,
-                        Key::R => self.chip8.keys[0xD] = false,
-                        Key::A => self.chip8.keys[0x7] = false,
-                        Key::S => self.chip8.keys[0x8] = false,
-                        Key::D => self.chip8.keys[0x9] = false,
-                        Key::F => self.chip8.keys[0xE] = false,
-                        Key::Z => self.chip8.keys[0xA] = false,
-                        Key::X => self.chip8.keys[0x0] = false,
-                        Key::C => self.chip8.keys[0xB] = false,
-                        Key::V => self.chip8.keys[0xF] = false,
-                        _ => {}
-                    },
+                    WindowEvent::Focus(false) => self.chip8.release_all_keys(),
+                    WindowEvent::Key(PAUSE_KEY, _, Action::Press, _) => {
+                        self.paused = !self.paused;
+                    }
+                    WindowEvent::Key(STEP_KEY, _, Action::Press | Action::Repeat, _) => {
+                        self.step = self.paused;
+                    }
+                    WindowEvent::Key(
+                        Key::Space,
+                        _,
+                        action @ (Action::Press | Action::Release),
+                        _,
+                    ) => {
+                        self.turbo = action == Action::Press;
+                    }
+                    WindowEvent::Key(key, _, action, _) => {
+                        let keypad = key_char(key).and_then(|c| self.layout.keypad_key(c));
+                        match (keypad, key_transition(action)) {
+                            (Some(key), Some(true)) => self.chip8.press_key(key),
+                            (Some(key), Some(false)) => self.chip8.release_key(key),
+                            _ => {}
+                        }
+                    }
                     _ => {}
                 }
             }
 
-            if current_time.duration_since(self.cpu_timer)
-                > Duration::from_nanos((1.0 / CHIP8_FREQ * 10_f32.powi(9)) as u64)
-            {
-                self.cpu_timer = current_time;
-                self.chip8.cycle();
+            // The pacer is polled even while paused so resuming doesn't
+            // replay the paused time as a burst of cycles.
+            let mut cycles = turbo_cycles(self.pacer.cpu_ticks(current_time), self.turbo);
+            if self.paused {
+                cycles = std::mem::take(&mut self.step) as u32;
+            }
+            for _ in 0..cycles {
+                if self.chip8.is_halted() {
+                    break;
+                }
+                if let Err(e) = self.chip8.cycle() {
+                    eprintln!("{e}");
+                    self.window.set_should_close(true);
+                    break;
+                }
+            }
+            if self.chip8.has_exited() {
+                self.window.set_should_close(true);
             }
 
-            if current_time.duration_since(self.timer)
-                >= Duration::from_nanos((1.0 / TIMER_FREQ * 10_f32.powi(9)) as u64)
-            {
-                self.timer = current_time;
-                self.chip8.timer();
-                self.update_texture(0xFF00FF00, 0);
-                self.render();
-                self.window.swap_buffers();
+            let timer_ticks = self.pacer.timer_ticks(current_time);
+            if timer_ticks > 0 {
+                if !self.paused {
+                    for _ in 0..timer_ticks {
+                        self.chip8.timer();
+                    }
+                }
+                self.present();
                 self.frame_count += 1;
-                //println!("FPS: {}", (self.frame_count as f64) / (current_time.duration_since(self.start_time).as_secs_f64()));
+                self.flush_trace();
             }
 
-            sleep(Duration::from_nanos(1_500_000));
+            sleep(self.pacer.sleep_duration(Instant::now()));
         }
+        // Escape, the close button and errors all leave the loop here, so
+        // the last frame's trace is written out whichever way it closed.
+        self.flush_trace();
+        Ok(())
+    }
+
+    fn flush_trace(&self) {
+        if let Some(Err(e)) = self.trace.as_ref().map(OpcodeTrace::flush) {
+            eprintln!("Could not write the opcode trace: {e}");
+        }
+    }
+
+    fn present(&mut self) {
+        let (fg_color, bg_color) = flash_colors(
+            self.fg_color,
+            self.bg_color,
+            self.flash && self.chip8.is_beeping(),
+        );
+        self.update_texture(texel(fg_color), texel(bg_color));
+        self.render();
+        self.window.swap_buffers();
     }
 
     pub fn render(&mut self) {
@@ -164,12 +216,8 @@ impl App {
     }
 
     fn update_texture(&mut self, f_color: u32, b_color: u32) {
-        for i in 0..(chip8::DISPLAY_WIDTH * chip8::DISPLAY_HEIGHT) {
-            if self.chip8.display[i] {
-                self.pixels[i] = f_color;
-            } else {
-                self.pixels[i] = b_color;
-            }
+        for (texel, on) in self.pixels.iter_mut().zip(self.chip8.display()) {
+            *texel = if on { f_color } else { b_color };
         }
 
         unsafe {
@@ -188,10 +236,36 @@ impl App {
     }
 }
 
+// Holding the turbo key runs extra cycles per tick; timers keep their 60Hz
+// cadence.
+fn turbo_cycles(ticks: u32, turbo: bool) -> u32 {
+    if turbo {
+        ticks * TURBO_MULTIPLIER
+    } else {
+        ticks
+    }
+}
+
+// Whether a key event presses (true) or releases (false) a keypad key.
+// Action::Repeat is dropped so a held key stays a single press.
+fn key_transition(action: Action) -> Option<bool> {
+    match action {
+        Action::Press => Some(true),
+        Action::Release => Some(false),
+        Action::Repeat => None,
+    }
+}
+
+fn texel(color: u32) -> u32 {
+    let [_, r, g, b] = color.to_be_bytes();
+    rgba(r, g, b, 0xFF)
+}
+
 struct GlContext {
     shader_program: GLuint,
     texture: GLuint,
     vao: GLuint,
+    vbo: GLuint,
 }
 
 impl GlContext {
@@ -232,10 +306,21 @@ impl GlContext {
             gl::EnableVertexAttribArray(0);
         }
 
+        let shader_program = Self::load_shader_program();
+        let sampler_name = CString::new("tex").unwrap();
+        unsafe {
+            gl::UseProgram(shader_program);
+            gl::Uniform1i(
+                gl::GetUniformLocation(shader_program, sampler_name.as_ptr()),
+                0,
+            );
+        }
+
         GlContext {
-            shader_program: Self::load_shader_program(),
+            shader_program,
             texture: Self::create_texture(),
             vao,
+            vbo,
         }
     }
     fn create_texture() -> u32 {
@@ -365,9 +450,83 @@ void main()
     fn draw(&self) {
         unsafe {
             gl::UseProgram(self.shader_program);
-            gl::BindTexture(gl::TEXTURE0, self.texture);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
             gl::BindVertexArray(self.vao);
             gl::DrawArrays(gl::TRIANGLES, 0, 6);
         }
     }
 }
+
+impl Drop for GlContext {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.shader_program);
+            gl::DeleteTextures(1, &self.texture);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
+
+fn key_char(key: Key) -> Option<char> {
+    Some(match key {
+        Key::Num0 | Key::Kp0 => '0',
+        Key::Num1 | Key::Kp1 => '1',
+        Key::Num2 | Key::Kp2 => '2',
+        Key::Num3 | Key::Kp3 => '3',
+        Key::Num4 | Key::Kp4 => '4',
+        Key::Num5 | Key::Kp5 => '5',
+        Key::Num6 | Key::Kp6 => '6',
+        Key::Num7 | Key::Kp7 => '7',
+        Key::Num8 | Key::Kp8 => '8',
+        Key::Num9 | Key::Kp9 => '9',
+        Key::A => 'A',
+        Key::B => 'B',
+        Key::C => 'C',
+        Key::D => 'D',
+        Key::E => 'E',
+        Key::F => 'F',
+        Key::G => 'G',
+        Key::H => 'H',
+        Key::I => 'I',
+        Key::J => 'J',
+        Key::K => 'K',
+        Key::L => 'L',
+        Key::M => 'M',
+        Key::N => 'N',
+        Key::O => 'O',
+        Key::P => 'P',
+        Key::Q => 'Q',
+        Key::R => 'R',
+        Key::S => 'S',
+        Key::T => 'T',
+        Key::U => 'U',
+        Key::V => 'V',
+        Key::W => 'W',
+        Key::X => 'X',
+        Key::Y => 'Y',
+        Key::Z => 'Z',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turbo_multiplies_the_due_cycles() {
+        assert_eq!(turbo_cycles(0, false), 0);
+        assert_eq!(turbo_cycles(7, false), 7);
+        assert_eq!(turbo_cycles(0, true), 0);
+        assert_eq!(turbo_cycles(7, true), 7 * TURBO_MULTIPLIER);
+    }
+
+    #[test]
+    fn key_repeat_is_not_a_transition() {
+        assert_eq!(key_transition(Action::Press), Some(true));
+        assert_eq!(key_transition(Action::Release), Some(false));
+        assert_eq!(key_transition(Action::Repeat), None);
+    }
+}
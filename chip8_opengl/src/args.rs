@@ -0,0 +1,128 @@
+use chip8::KeyLayout;
+use chip8_util::{parse_color, GameSettings, QuirksPreset};
+
+const DEFAULT_FG_COLOR: u32 = 0x00FF00;
+const DEFAULT_BG_COLOR: u32 = 0x000000;
+
+// Options left unset fall back to the ROM's saved settings, then to the
+// defaults.
+pub struct Args {
+    // Without a ROM the built-in splash program runs.
+    pub rom: Option<String>,
+    pub fg_color: Option<u32>,
+    pub bg_color: Option<u32>,
+    pub clock_hz: Option<u32>,
+    pub quirks: Option<QuirksPreset>,
+    // Invert the display while the sound timer runs.
+    pub flash: bool,
+    // Start with execution stopped at the first instruction.
+    pub paused: bool,
+    // Append every executed instruction to this file.
+    pub log_opcodes: Option<String>,
+    // Which keyboard keys stand in for the hex keypad.
+    pub layout: KeyLayout,
+}
+
+impl Args {
+    pub fn parse() -> Self {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    fn parse_from(mut args: impl Iterator<Item = String>) -> Self {
+        let mut rom = None;
+        let mut fg_color = None;
+        let mut bg_color = None;
+        let mut clock_hz = None;
+        let mut quirks = None;
+        let mut flash = false;
+        let mut paused = false;
+        let mut log_opcodes = None;
+        let mut layout = KeyLayout::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--fg" => fg_color = Some(Self::color(args.next(), "--fg")),
+                "--bg" => bg_color = Some(Self::color(args.next(), "--bg")),
+                "--clock" => {
+                    clock_hz = Some(
+                        args.next()
+                            .and_then(|hz| hz.parse().ok())
+                            .expect("--clock expects a frequency in Hz."),
+                    )
+                }
+                "--quirks" => {
+                    quirks = Some(
+                        args.next()
+                            .as_deref()
+                            .and_then(QuirksPreset::parse)
+                            .expect("--quirks expects one of default, chip8, schip or octo."),
+                    )
+                }
+                "--flash" => flash = true,
+                "--paused" => paused = true,
+                "--log-opcodes" => {
+                    log_opcodes = Some(args.next().expect("--log-opcodes expects a file path."))
+                }
+                "--layout" => {
+                    layout = args
+                        .next()
+                        .as_deref()
+                        .and_then(KeyLayout::parse)
+                        .expect("--layout expects one of classic, number-row or azerty.")
+                }
+                _ => rom = Some(arg),
+            }
+        }
+
+        Args {
+            rom,
+            fg_color,
+            bg_color,
+            clock_hz,
+            quirks,
+            flash,
+            paused,
+            log_opcodes,
+            layout,
+        }
+    }
+
+    pub fn settings(&self, saved: Option<GameSettings>) -> GameSettings {
+        let saved = saved.unwrap_or(GameSettings {
+            clock_hz: chip8::DEFAULT_CLOCK_HZ,
+            quirks: QuirksPreset::Default,
+            fg_color: DEFAULT_FG_COLOR,
+            bg_color: DEFAULT_BG_COLOR,
+        });
+        GameSettings {
+            clock_hz: self.clock_hz.unwrap_or(saved.clock_hz),
+            quirks: self.quirks.unwrap_or(saved.quirks),
+            fg_color: self.fg_color.unwrap_or(saved.fg_color),
+            bg_color: self.bg_color.unwrap_or(saved.bg_color),
+        }
+    }
+
+    fn color(value: Option<String>, flag: &str) -> u32 {
+        value
+            .as_deref()
+            .and_then(parse_color)
+            .unwrap_or_else(|| panic!("{flag} expects a color in RRGGBB format."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn paused_starts_stopped_at_the_first_instruction() {
+        let args = parse(&["--paused", "game.ch8"]);
+        assert!(args.paused);
+        assert_eq!(args.rom.as_deref(), Some("game.ch8"));
+        assert!(!parse(&["game.ch8"]).paused);
+    }
+}
@@ -1,10 +1,45 @@
 mod app;
+mod args;
 
 mod gl {
     include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 }
 
+use chip8_util::SettingsStore;
+
 fn main() {
-    let mut app = app::App::new();
-    app.run();
+    let args = args::Args::parse();
+    // Read once: the same bytes key the settings and get loaded.
+    let rom = match args.rom.as_ref().map(std::fs::read).transpose() {
+        Ok(rom) => rom,
+        Err(e) => {
+            eprintln!("Could not read the ROM: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    // The splash program shown without a ROM has no saved settings.
+    let mut store = rom.as_ref().and_then(|_| {
+        SettingsStore::open_default()
+            .map_err(|e| eprintln!("Could not load settings: {e}"))
+            .ok()
+    });
+    let saved = store
+        .as_ref()
+        .zip(rom.as_ref())
+        .and_then(|(store, rom)| store.get(rom));
+    let settings = args.settings(saved);
+
+    let mut app = app::App::new(&args, &settings);
+    if let Err(e) = app.run(rom.as_deref()) {
+        eprintln!("Could not load the ROM: {e}");
+        std::process::exit(1);
+    }
+
+    if let (Some(store), Some(rom)) = (&mut store, &rom) {
+        store.set(rom, settings);
+        if let Err(e) = store.save() {
+            eprintln!("Could not save settings: {e}");
+        }
+    }
 }
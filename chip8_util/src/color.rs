@@ -0,0 +1,29 @@
+// Parses an `RRGGBB` color, optionally prefixed with `#` or `0x`, into
+// `0x00RRGGBB`.
+pub fn parse_color(s: &str) -> Option<u32> {
+    let hex = s
+        .strip_prefix('#')
+        .or_else(|| s.strip_prefix("0x"))
+        .unwrap_or(s);
+    // from_str_radix alone would also take a leading '+'.
+    if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+// Visual bell for hosts without audio: swaps the two colors while the sound
+// timer is running.
+pub fn flash_colors(fg_color: u32, bg_color: u32, beeping: bool) -> (u32, u32) {
+    if beeping {
+        (bg_color, fg_color)
+    } else {
+        (fg_color, bg_color)
+    }
+}
+
+// Packs a texel for RGBA8 textures: the channels land in memory in R, G, B, A
+// order whatever the host's byte order.
+pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> u32 {
+    u32::from_ne_bytes([r, g, b, a])
+}
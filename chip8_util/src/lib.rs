@@ -0,0 +1,9 @@
+mod color;
+mod pacer;
+mod settings;
+mod trace;
+
+pub use color::{flash_colors, parse_color, rgba};
+pub use pacer::FramePacer;
+pub use settings::{GameSettings, QuirksPreset, SettingsStore};
+pub use trace::OpcodeTrace;
@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+// How many timer periods the pacer may fall behind before it drops the
+// backlog and resynchronizes instead of trying to catch up.
+const MAX_LAG: u32 = 4;
+
+pub struct FramePacer {
+    cpu_period: Duration,
+    timer_period: Duration,
+    next_cpu: Instant,
+    next_timer: Instant,
+    max_cpu_ticks: u32,
+}
+
+impl FramePacer {
+    pub fn new(cpu_hz: u32, timer_hz: u32, now: Instant) -> Self {
+        let cpu_period = Duration::from_secs(1) / cpu_hz.max(1);
+        let timer_period = Duration::from_secs(1) / timer_hz.max(1);
+        FramePacer {
+            cpu_period,
+            timer_period,
+            next_cpu: now + cpu_period,
+            next_timer: now + timer_period,
+            max_cpu_ticks: MAX_LAG * (cpu_hz / timer_hz.max(1)).max(1),
+        }
+    }
+
+    pub fn cpu_ticks(&mut self, now: Instant) -> u32 {
+        Self::due(&mut self.next_cpu, self.cpu_period, now, self.max_cpu_ticks)
+    }
+
+    pub fn timer_ticks(&mut self, now: Instant) -> u32 {
+        Self::due(&mut self.next_timer, self.timer_period, now, MAX_LAG)
+    }
+
+    pub fn next_deadline(&self) -> Instant {
+        self.next_cpu.min(self.next_timer)
+    }
+
+    // For callers that batch CPU cycles per timer tick and never poll
+    // `cpu_ticks`.
+    pub fn next_timer_deadline(&self) -> Instant {
+        self.next_timer
+    }
+
+    pub fn sleep_duration(&self, now: Instant) -> Duration {
+        self.next_deadline().saturating_duration_since(now)
+    }
+
+    fn due(next: &mut Instant, period: Duration, now: Instant, max_ticks: u32) -> u32 {
+        if now < *next {
+            return 0;
+        }
+        let behind = now - *next;
+        let ticks = (behind.as_nanos() / period.as_nanos()) as u32 + 1;
+        if ticks > max_ticks {
+            *next = now + period;
+            return max_ticks;
+        }
+        *next += period * ticks;
+        ticks
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use chip8::Quirks;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuirksPreset {
+    Default,
+    Chip8,
+    Schip,
+    Octo,
+}
+
+impl QuirksPreset {
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "default" => QuirksPreset::Default,
+            "chip8" => QuirksPreset::Chip8,
+            "schip" => QuirksPreset::Schip,
+            "octo" => QuirksPreset::Octo,
+            _ => return None,
+        })
+    }
+
+    pub fn quirks(self) -> Quirks {
+        match self {
+            QuirksPreset::Default => Quirks::default(),
+            QuirksPreset::Chip8 => Quirks::chip8(),
+            QuirksPreset::Schip => Quirks::schip(),
+            QuirksPreset::Octo => Quirks::octo(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameSettings {
+    pub clock_hz: u32,
+    pub quirks: QuirksPreset,
+    // Colors in `0x00RRGGBB`, as returned by `parse_color`.
+    pub fg_color: u32,
+    pub bg_color: u32,
+}
+
+// Per-ROM settings kept in a JSON file and keyed by `chip8::rom_hash`, so a
+// renamed ROM keeps its settings.
+pub struct SettingsStore {
+    path: PathBuf,
+    games: BTreeMap<String, GameSettings>,
+}
+
+impl SettingsStore {
+    // settings.json in the platform config directory, e.g.
+    // ~/.config/chip8_rs on Linux.
+    pub fn open_default() -> io::Result<Self> {
+        let dirs = ProjectDirs::from("", "", "chip8_rs")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory found."))?;
+        Self::open(dirs.config_dir().join("settings.json"))
+    }
+
+    // A missing file opens as an empty store.
+    pub fn open<P: Into<PathBuf>>(path: P) -> io::Result<Self> {
+        let path = path.into();
+        let games = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(SettingsStore { path, games })
+    }
+
+    pub fn get(&self, rom: &[u8]) -> Option<GameSettings> {
+        self.games.get(&chip8::rom_hash(rom)).copied()
+    }
+
+    pub fn set(&mut self, rom: &[u8], settings: GameSettings) {
+        self.games.insert(chip8::rom_hash(rom), settings);
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.games)?)
+    }
+}
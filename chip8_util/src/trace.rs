@@ -0,0 +1,47 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use chip8::Chip8;
+
+// Buffered opcode log shared with the step callback. Frontends flush it once
+// per frame, so a crash loses at most one frame of trace, and once more on
+// exit. Flushing again with nothing new written is a no-op.
+pub struct OpcodeTrace(Arc<Mutex<TraceFile>>);
+
+struct TraceFile {
+    writer: BufWriter<File>,
+    // The step callback can't return errors, so the first one waits here
+    // for the next flush.
+    error: Option<io::Error>,
+}
+
+impl OpcodeTrace {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(OpcodeTrace(Arc::new(Mutex::new(TraceFile {
+            writer: BufWriter::new(File::create(path)?),
+            error: None,
+        }))))
+    }
+
+    pub fn install(&self, chip8: &mut Chip8) {
+        let file = Arc::clone(&self.0);
+        chip8.set_on_step(Some(Arc::new(move |pc, opcode| {
+            let mut file = file.lock().unwrap();
+            if file.error.is_none() {
+                if let Err(e) = writeln!(file.writer, "{}", chip8::trace_line(pc, opcode)) {
+                    file.error = Some(e);
+                }
+            }
+        })));
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        let mut file = self.0.lock().unwrap();
+        if let Some(e) = file.error.take() {
+            return Err(e);
+        }
+        file.writer.flush()
+    }
+}
@@ -0,0 +1,42 @@
+use chip8_util::{flash_colors, parse_color, rgba};
+
+#[test]
+fn parses_rrggbb_with_or_without_prefix() {
+    assert_eq!(parse_color("00FF00"), Some(0x00FF00));
+    assert_eq!(parse_color("#1a2b3c"), Some(0x1A2B3C));
+    assert_eq!(parse_color("0xFFFFFF"), Some(0xFFFFFF));
+}
+
+#[test]
+fn rejects_anything_but_six_hex_digits() {
+    assert_eq!(parse_color("FFF"), None);
+    assert_eq!(parse_color("FFFFFFF"), None);
+    assert_eq!(parse_color("GGGGGG"), None);
+    assert_eq!(parse_color("+FFFFF"), None);
+    assert_eq!(parse_color("#-FFFFF"), None);
+    assert_eq!(parse_color(""), None);
+}
+
+#[test]
+fn rgba_lays_out_bytes_in_channel_order() {
+    assert_eq!(
+        rgba(0x11, 0x22, 0x33, 0x44).to_ne_bytes(),
+        [0x11, 0x22, 0x33, 0x44]
+    );
+    assert_eq!(
+        rgba(0xFF, 0x00, 0x00, 0xFF).to_ne_bytes(),
+        [0xFF, 0x00, 0x00, 0xFF]
+    );
+}
+
+#[test]
+fn flash_colors_inverts_only_while_beeping() {
+    let (fg, bg) = (0xFFFFFF, 0x000000);
+    assert_eq!(flash_colors(fg, bg, false), (fg, bg));
+    assert_eq!(flash_colors(fg, bg, true), (bg, fg));
+    assert_eq!(flash_colors(0x123456, 0xABCDEF, true), (0xABCDEF, 0x123456));
+    assert_eq!(
+        flash_colors(0x123456, 0xABCDEF, false),
+        (0x123456, 0xABCDEF)
+    );
+}
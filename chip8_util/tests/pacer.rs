@@ -0,0 +1,36 @@
+use std::time::{Duration, Instant};
+
+use chip8_util::FramePacer;
+
+const MS: Duration = Duration::from_millis(1);
+
+#[test]
+fn nothing_is_due_before_the_first_deadline() {
+    let start = Instant::now();
+    let mut pacer = FramePacer::new(1000, 100, start);
+    assert_eq!(pacer.cpu_ticks(start), 0);
+    assert_eq!(pacer.timer_ticks(start), 0);
+    assert_eq!(pacer.next_deadline(), start + MS);
+    assert_eq!(pacer.sleep_duration(start), MS);
+}
+
+#[test]
+fn overruns_are_caught_up_within_the_lag_limit() {
+    let start = Instant::now();
+    let mut pacer = FramePacer::new(1000, 100, start);
+    assert_eq!(pacer.timer_ticks(start + 25 * MS), 2);
+    // The remainder carries over: the next tick is due at 30ms.
+    assert_eq!(pacer.next_timer_deadline(), start + 30 * MS);
+    assert_eq!(pacer.cpu_ticks(start + 5 * MS), 5);
+    assert_eq!(pacer.next_deadline(), start + 6 * MS);
+}
+
+#[test]
+fn a_long_stall_resynchronizes_instead_of_bursting() {
+    let start = Instant::now();
+    let mut pacer = FramePacer::new(1000, 100, start);
+    let now = start + Duration::from_secs(1);
+    assert_eq!(pacer.timer_ticks(now), 4);
+    assert_eq!(pacer.next_timer_deadline(), now + 10 * MS);
+    assert_eq!(pacer.cpu_ticks(now), 40);
+}
@@ -0,0 +1,30 @@
+use chip8_util::{GameSettings, QuirksPreset, SettingsStore};
+
+#[test]
+fn settings_round_trip_through_the_file() {
+    let dir = std::env::temp_dir().join(format!("chip8-settings-{}", std::process::id()));
+    let path = dir.join("settings.json");
+    let settings = GameSettings {
+        clock_hz: 1200,
+        quirks: QuirksPreset::Schip,
+        fg_color: 0xFFB000,
+        bg_color: 0x202020,
+    };
+
+    let mut store = SettingsStore::open(&path).unwrap();
+    assert_eq!(store.get(b"game"), None);
+    store.set(b"game", settings);
+    store.save().unwrap();
+
+    let reopened = SettingsStore::open(&path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(reopened.get(b"game"), Some(settings));
+    assert_eq!(reopened.get(b"another game"), None);
+}
+
+#[test]
+fn quirks_presets_parse_by_name() {
+    assert_eq!(QuirksPreset::parse("schip"), Some(QuirksPreset::Schip));
+    assert_eq!(QuirksPreset::parse("Octo"), Some(QuirksPreset::Octo));
+    assert_eq!(QuirksPreset::parse("xo-chip"), None);
+}
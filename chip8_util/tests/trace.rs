@@ -0,0 +1,20 @@
+use chip8::Chip8;
+use chip8_util::OpcodeTrace;
+
+#[test]
+fn flushing_twice_writes_the_trace_once() {
+    let path = std::env::temp_dir().join(format!("chip8-trace-{}.txt", std::process::id()));
+    let trace = OpcodeTrace::create(&path).unwrap();
+    let mut chip8 = Chip8::with_seed(0);
+    // CLS; LD V0, 5
+    chip8.load_from_bytes(&[0x00, 0xE0, 0x60, 0x05]).unwrap();
+    trace.install(&mut chip8);
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+
+    trace.flush().unwrap();
+    trace.flush().unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(text, "200  00E0  CLS\n202  6005  LD V0, 0x05\n");
+}
@@ -0,0 +1,92 @@
+use chip8::KeyLayout;
+
+pub struct Args {
+    // Without a ROM the built-in splash program runs.
+    pub rom: Option<String>,
+    // Print a disassembly listing instead of running the ROM.
+    pub disasm: bool,
+    // Invert the display while the sound timer runs.
+    pub flash: bool,
+    // Scale the display by whole multiples only.
+    pub integer_scale: bool,
+    // Run this many timer ticks, then save the display to `out` and exit.
+    pub frames: Option<u64>,
+    pub out: Option<String>,
+    // Save the rendered window to `out` instead of the upscaled display.
+    pub capture_window: bool,
+    // Append every executed instruction to this file.
+    pub log_opcodes: Option<String>,
+    // Which keyboard keys stand in for the hex keypad.
+    pub layout: KeyLayout,
+    // Some(true) waits for vblank, Some(false) favors latency; unset leaves
+    // the choice to the adapter.
+    pub vsync: Option<bool>,
+}
+
+impl Args {
+    pub fn parse() -> Self {
+        let mut rom = None;
+        let mut disasm = false;
+        let mut flash = false;
+        let mut integer_scale = false;
+        let mut frames = None;
+        let mut out = None;
+        let mut capture_window = false;
+        let mut log_opcodes = None;
+        let mut layout = KeyLayout::default();
+        let mut vsync = None;
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--disasm" => disasm = true,
+                "--flash" => flash = true,
+                "--integer-scale" => integer_scale = true,
+                "--frames" => {
+                    frames = Some(
+                        args.next()
+                            .and_then(|n| n.parse().ok())
+                            .expect("--frames expects a number of frames."),
+                    )
+                }
+                "--out" => out = Some(args.next().expect("--out expects a file path.")),
+                "--capture-window" => capture_window = true,
+                "--log-opcodes" => {
+                    log_opcodes = Some(args.next().expect("--log-opcodes expects a file path."))
+                }
+                "--layout" => {
+                    layout = args
+                        .next()
+                        .as_deref()
+                        .and_then(KeyLayout::parse)
+                        .expect("--layout expects one of classic, number-row or azerty.")
+                }
+                "--vsync" => {
+                    vsync = Some(match args.next().as_deref() {
+                        Some("on") => true,
+                        Some("off") => false,
+                        _ => panic!("--vsync expects on or off."),
+                    })
+                }
+                _ => rom = Some(arg),
+            }
+        }
+
+        if out.is_some() && frames.is_none() {
+            panic!("--out requires --frames.");
+        }
+
+        Args {
+            rom,
+            disasm,
+            flash,
+            integer_scale,
+            frames,
+            out,
+            capture_window,
+            log_opcodes,
+            layout,
+            vsync,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use chip8::{DISPLAY_HEIGHT, DISPLAY_SIZE, DISPLAY_WIDTH};
+use image::{ImageResult, RgbaImage};
+use winit::dpi::PhysicalSize;
+
+const SCALE: u32 = 10;
+
+// Writes the display as a PNG, upscaled so every CHIP-8 pixel is a SCALE x
+// SCALE block. Colors are RGBA texels as passed to `Renderer::render`.
+pub fn save_png<P: AsRef<Path>>(
+    display: &[bool; DISPLAY_SIZE],
+    fg_color: u32,
+    bg_color: u32,
+    path: P,
+) -> ImageResult<()> {
+    let width = DISPLAY_WIDTH as u32 * SCALE;
+    let height = DISPLAY_HEIGHT as u32 * SCALE;
+    let image = RgbaImage::from_fn(width, height, |x, y| {
+        let index = (x / SCALE) as usize + DISPLAY_WIDTH * (y / SCALE) as usize;
+        let texel = if display[index] { fg_color } else { bg_color };
+        image::Rgba(texel.to_ne_bytes())
+    });
+    image.save(path)
+}
+
+// Writes a frame read back with `Renderer::capture_frame`.
+pub fn save_frame<P: AsRef<Path>>(
+    rgba: Vec<u8>,
+    size: PhysicalSize<u32>,
+    path: P,
+) -> ImageResult<()> {
+    let image = RgbaImage::from_raw(size.width, size.height, rgba)
+        .expect("Captured frame does not match the surface size.");
+    image.save(path)
+}
@@ -1,33 +1,59 @@
-use std::time::{Duration, Instant};
+use std::time::Instant;
+
+use chip8_util::{flash_colors, rgba, FramePacer, OpcodeTrace};
 use winit::{
     event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     window::WindowBuilder,
 };
+mod args;
+mod capture;
 mod renderer;
+use args::Args;
 use renderer::Renderer;
 
-const CPU_FREQ: f32 = 800.0;
-const TIMER_FREQ: f32 = 60.0;
-
-const CPU_DURATION: Duration = Duration::from_micros((1.0 / CPU_FREQ * 1_000_000.0) as u64);
-const TIMER_DURATION: Duration = Duration::from_micros((1.0 / TIMER_FREQ * 1_000_000.0) as u64);
+const TIMER_FREQ: u32 = 60;
+const FG_COLOR: u32 = rgba(0x00, 0xFF, 0x00, 0xFF);
+const BG_COLOR: u32 = rgba(0x00, 0x00, 0x00, 0xFF);
 
 fn main() {
     env_logger::init();
-    let path = std::env::args().nth(1).expect("No ROM path is provided.");
+    let args = Args::parse();
+    if args.disasm {
+        let rom = std::fs::read(args.rom.as_ref().expect("--disasm needs a ROM path.")).unwrap();
+        print!("{}", chip8::disassemble(&rom, 0x200));
+        return;
+    }
+
     let mut chip8 = chip8::Chip8::new();
-    chip8.load(path).unwrap();
+    match &args.rom {
+        Some(path) => chip8.load(path).unwrap(),
+        None => chip8.load_default(),
+    }
+
+    let trace = args
+        .log_opcodes
+        .as_ref()
+        .and_then(|path| match OpcodeTrace::create(path) {
+            Ok(trace) => Some(trace),
+            Err(e) => {
+                log::error!("Could not open {path}: {e}");
+                None
+            }
+        });
+    if let Some(trace) = &trace {
+        trace.install(&mut chip8);
+    }
 
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new().build(&event_loop).unwrap();
-    let mut renderer = Renderer::new(&window).unwrap();
+    let mut renderer = Renderer::new(&window, args.integer_scale, args.vsync).unwrap();
 
-    let start_time = Instant::now();
-    let mut cpu_timer = start_time;
-    let mut timer = start_time;
+    let mut pacer = FramePacer::new(chip8.clock_hz(), TIMER_FREQ, Instant::now());
+    let mut last_frame = Instant::now();
 
     event_loop.run(move |event, _, control_flow| match event {
+        #[allow(clippy::collapsible_match)]
         Event::WindowEvent { window_id, event } => {
             if window_id == window.id() {
                 match event {
@@ -47,6 +73,7 @@ fn main() {
                     WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
                         renderer.resize(Some(*new_inner_size));
                     }
+                    WindowEvent::Focused(false) => chip8.release_all_keys(),
                     WindowEvent::KeyboardInput {
                         input:
                             KeyboardInput {
@@ -55,73 +82,153 @@ fn main() {
                                 ..
                             },
                         ..
-                    } => match state {
-                        ElementState::Pressed => match keycode {
-                            VirtualKeyCode::Key1 => chip8.keys[0x1] = true,
-                            VirtualKeyCode::Key2 => chip8.keys[0x2] = true,
-                            VirtualKeyCode::Key3 => chip8.keys[0x3] = true,
-                            VirtualKeyCode::Key4 => chip8.keys[0xC] = true,
-                            VirtualKeyCode::Q => chip8.keys[0x4] = true,
-                            VirtualKeyCode::W => chip8.keys[0x5] = true,
-                            VirtualKeyCode::E => chip8.keys[0x6] = true,
-                            VirtualKeyCode::R => chip8.keys[0xD] = true,
-                            VirtualKeyCode::A => chip8.keys[0x7] = true,
-                            VirtualKeyCode::S => chip8.keys[0x8] = true,
-                            VirtualKeyCode::D => chip8.keys[0x9] = true,
-                            VirtualKeyCode::F => chip8.keys[0xE] = true,
-                            VirtualKeyCode::Z => chip8.keys[0xA] = true,
-                            VirtualKeyCode::X => chip8.keys[0x0] = true,
-                            VirtualKeyCode::C => chip8.keys[0xB] = true,
-                            VirtualKeyCode::V => chip8.keys[0xF] = true,
-                            _ => {}
-                        },
-                        ElementState::Released => match keycode {
-                            VirtualKeyCode::Key1 => chip8.keys[0x1] = false,
-                            VirtualKeyCode::Key2 => chip8.keys[0x2] = false,
-                            VirtualKeyCode::Key3 => chip8.keys[0x3] = false,
-                            VirtualKeyCode::Key4 => chip8.keys[0xC] = false,
-                            VirtualKeyCode::Q => chip8.keys[0x4] = false,
-                            VirtualKeyCode::W => chip8.keys[0x5] = false,
-                            VirtualKeyCode::E => chip8.keys[0x6] = false,
-                            VirtualKeyCode::R => chip8.keys[0xD] = false,
-                            VirtualKeyCode::A => chip8.keys[0x7] = false,
-                            VirtualKeyCode::S => chip8.keys[0x8] = false,
-                            VirtualKeyCode::D => chip8.keys[0x9] = false,
-                            VirtualKeyCode::F => chip8.keys[0xE] = false,
-                            VirtualKeyCode::Z => chip8.keys[0xA] = false,
-                            VirtualKeyCode::X => chip8.keys[0x0] = false,
-                            VirtualKeyCode::C => chip8.keys[0xB] = false,
-                            VirtualKeyCode::V => chip8.keys[0xF] = false,
-                            _ => {}
-                        },
-                    },
+                    } => {
+                        if let Some(key) =
+                            keycode_char(keycode).and_then(|c| args.layout.keypad_key(c))
+                        {
+                            match key_transition(chip8.is_key_pressed(key), state) {
+                                Some(true) => chip8.press_key(key),
+                                Some(false) => chip8.release_key(key),
+                                None => {}
+                            }
+                        }
+                    }
                     _ => {}
                 }
             }
         }
         Event::MainEventsCleared => {
             let current_time = Instant::now();
-            if current_time.duration_since(cpu_timer) >= CPU_DURATION {
-                cpu_timer = current_time;
-                chip8.cycle();
-            }
-
-            if current_time.duration_since(timer) >= TIMER_DURATION {
+            let due_frames = pacer.timer_ticks(current_time);
+            if due_frames > 0 {
                 log::trace!(
                     "FPS: {}",
-                    1.0 / (current_time.duration_since(timer).as_secs_f64())
+                    1.0 / (current_time.duration_since(last_frame).as_secs_f64())
                 );
-                timer = current_time;
-                chip8.timer();
-                match renderer.render(&chip8.display, 0xFF00FF00, 0) {
+                last_frame = current_time;
+                for _ in 0..due_frames {
+                    if let Err(e) = chip8.step_frame() {
+                        log::error!("{e}");
+                        *control_flow = ControlFlow::Exit;
+                        break;
+                    }
+                }
+                if chip8.has_exited() {
+                    *control_flow = ControlFlow::Exit;
+                }
+                if let Some(Err(e)) = trace.as_ref().map(OpcodeTrace::flush) {
+                    log::error!("Could not write the opcode trace: {e}");
+                }
+
+                let (fg_color, bg_color) =
+                    flash_colors(FG_COLOR, BG_COLOR, args.flash && chip8.is_beeping());
+                renderer.set_colors(fg_color, bg_color);
+                match renderer.render(&chip8.display()) {
                     Ok(_) => {}
                     Err(wgpu::SurfaceError::Lost) => renderer.resize(None),
                     Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
                     Err(e) => eprintln!("{:?}", e),
                 }
+
+                if args
+                    .frames
+                    .is_some_and(|frames| chip8.frame_count() >= frames)
+                {
+                    if let Some(out) = &args.out {
+                        let saved = if args.capture_window {
+                            capture::save_frame(
+                                renderer.capture_frame(),
+                                renderer.surface_size(),
+                                out,
+                            )
+                        } else {
+                            capture::save_png(&chip8.display(), FG_COLOR, BG_COLOR, out)
+                        };
+                        if let Err(e) = saved {
+                            log::error!("{e}");
+                        }
+                    }
+                    *control_flow = ControlFlow::Exit;
+                }
+            }
+
+            if *control_flow != ControlFlow::Exit {
+                *control_flow = ControlFlow::WaitUntil(pacer.next_timer_deadline());
+            }
+        }
+        // Every exit path, Escape and the close button included, ends here.
+        Event::LoopDestroyed => {
+            if let Some(Err(e)) = trace.as_ref().map(OpcodeTrace::flush) {
+                log::error!("Could not write the opcode trace: {e}");
             }
-            std::thread::sleep(Duration::from_nanos(1_300_000));
         }
         _ => {}
     });
 }
+
+// Whether a key event presses (true) or releases (false) a keypad key that
+// is currently `held`. winit reports key repeat as more Pressed events, so
+// those are dropped along with releases of keys that are already up.
+fn key_transition(held: bool, state: ElementState) -> Option<bool> {
+    match state {
+        ElementState::Pressed if !held => Some(true),
+        ElementState::Released if held => Some(false),
+        _ => None,
+    }
+}
+
+fn keycode_char(keycode: VirtualKeyCode) -> Option<char> {
+    Some(match keycode {
+        VirtualKeyCode::Key0 => '0',
+        VirtualKeyCode::Key1 => '1',
+        VirtualKeyCode::Key2 => '2',
+        VirtualKeyCode::Key3 => '3',
+        VirtualKeyCode::Key4 => '4',
+        VirtualKeyCode::Key5 => '5',
+        VirtualKeyCode::Key6 => '6',
+        VirtualKeyCode::Key7 => '7',
+        VirtualKeyCode::Key8 => '8',
+        VirtualKeyCode::Key9 => '9',
+        VirtualKeyCode::A => 'A',
+        VirtualKeyCode::B => 'B',
+        VirtualKeyCode::C => 'C',
+        VirtualKeyCode::D => 'D',
+        VirtualKeyCode::E => 'E',
+        VirtualKeyCode::F => 'F',
+        VirtualKeyCode::G => 'G',
+        VirtualKeyCode::H => 'H',
+        VirtualKeyCode::I => 'I',
+        VirtualKeyCode::J => 'J',
+        VirtualKeyCode::K => 'K',
+        VirtualKeyCode::L => 'L',
+        VirtualKeyCode::M => 'M',
+        VirtualKeyCode::N => 'N',
+        VirtualKeyCode::O => 'O',
+        VirtualKeyCode::P => 'P',
+        VirtualKeyCode::Q => 'Q',
+        VirtualKeyCode::R => 'R',
+        VirtualKeyCode::S => 'S',
+        VirtualKeyCode::T => 'T',
+        VirtualKeyCode::U => 'U',
+        VirtualKeyCode::V => 'V',
+        VirtualKeyCode::W => 'W',
+        VirtualKeyCode::X => 'X',
+        VirtualKeyCode::Y => 'Y',
+        VirtualKeyCode::Z => 'Z',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_repeat_is_not_a_transition() {
+        assert_eq!(key_transition(false, ElementState::Pressed), Some(true));
+        // A repeat arrives as another press while the key is down.
+        assert_eq!(key_transition(true, ElementState::Pressed), None);
+        assert_eq!(key_transition(true, ElementState::Released), Some(false));
+        assert_eq!(key_transition(false, ElementState::Released), None);
+    }
+}
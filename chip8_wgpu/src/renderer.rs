@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chip8_util::rgba;
 use wgpu::util::DeviceExt;
 use winit::window::Window;
 
@@ -14,10 +15,33 @@ pub struct Renderer {
     chip8_bind_group: wgpu::BindGroup,
     render_pipeline: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
+    integer_scale: bool,
+    palette: Palette,
+}
+
+// Texel colors for lit and dark pixels. The background is also the clear
+// color around the display quad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Palette {
+    fg_color: u32,
+    bg_color: u32,
+}
+
+impl Palette {
+    fn set(&mut self, fg_color: u32, bg_color: u32) {
+        self.fg_color = fg_color;
+        self.bg_color = bg_color;
+    }
+
+    fn fill(&self, texels: &mut [u32], display: &[bool]) {
+        for (texel, &on) in texels.iter_mut().zip(display) {
+            *texel = if on { self.fg_color } else { self.bg_color };
+        }
+    }
 }
 
 impl Renderer {
-    pub fn new(window: &Window) -> Result<Self> {
+    pub fn new(window: &Window, integer_scale: bool, vsync: Option<bool>) -> Result<Self> {
         let surface_size = window.inner_size();
 
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
@@ -49,8 +73,7 @@ impl Renderer {
             .formats
             .iter()
             .copied()
-            .filter(|f| f.describe().srgb)
-            .next()
+            .find(|f| f.describe().srgb)
             .unwrap_or(surface_caps.formats[0]);
 
         let surface_config = wgpu::SurfaceConfiguration {
@@ -58,7 +81,7 @@ impl Renderer {
             format: surface_format,
             width: surface_size.width,
             height: surface_size.height,
-            present_mode: surface_caps.present_modes[0],
+            present_mode: present_mode(&surface_caps.present_modes, vsync),
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
         };
@@ -220,24 +243,11 @@ impl Renderer {
             multiview: None,
         });
 
-        let vertices: Vec<f32> = vec![
-            -1.0, -1.0, 0.0, 1.0,
-             1.0, -1.0, 1.0, 1.0,
-             1.0,  1.0, 1.0, 0.0,
-             1.0,  1.0, 1.0, 0.0,
-            -1.0,  1.0, 0.0, 0.0,
-            -1.0, -1.0, 0.0, 1.0,
-        ];
-
+        let vertices = quad_vertices(surface_size, integer_scale);
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("vertex_buffer"),
-            contents: unsafe {
-                std::slice::from_raw_parts(
-                    vertices.as_ptr() as *const u8,
-                    vertices.len() * std::mem::size_of::<f32>(),
-                )
-            },
-            usage: wgpu::BufferUsages::VERTEX,
+            contents: vertex_bytes(&vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
         Ok(Self {
@@ -252,9 +262,18 @@ impl Renderer {
             chip8_bind_group,
             render_pipeline,
             vertex_buffer,
+            integer_scale,
+            palette: Palette {
+                fg_color: rgba(0xFF, 0xFF, 0xFF, 0xFF),
+                bg_color: rgba(0x00, 0x00, 0x00, 0xFF),
+            },
         })
     }
 
+    pub fn surface_size(&self) -> winit::dpi::PhysicalSize<u32> {
+        self.surface_size
+    }
+
     pub fn resize(&mut self, new_size: Option<winit::dpi::PhysicalSize<u32>>) {
         let new_size = new_size.unwrap_or(self.surface_size);
         if new_size.width > 0 && new_size.height > 0 {
@@ -262,23 +281,23 @@ impl Renderer {
             self.surface_config.width = new_size.width;
             self.surface_config.height = new_size.height;
             self.surface.configure(&self.device, &self.surface_config);
+            let vertices = quad_vertices(new_size, self.integer_scale);
+            self.queue
+                .write_buffer(&self.vertex_buffer, 0, vertex_bytes(&vertices));
             log::info!("Screen resize: ({},{})", new_size.width, new_size.height);
         }
     }
 
+    // Takes effect from the next `render`.
+    pub fn set_colors(&mut self, fg_color: u32, bg_color: u32) {
+        self.palette.set(fg_color, bg_color);
+    }
+
     pub fn render(
         &mut self,
         chip8_display: &[bool; chip8::DISPLAY_SIZE],
-        fg_color: u32,
-        bg_color: u32,
     ) -> Result<(), wgpu::SurfaceError> {
-        for i in 0..chip8::DISPLAY_SIZE {
-            if chip8_display[i] {
-                self.chip8_pixels[i] = fg_color;
-            } else {
-                self.chip8_pixels[i] = bg_color;
-            }
-        }
+        self.palette.fill(&mut self.chip8_pixels, chip8_display);
         let chip8_pixels_slice = unsafe {
             std::slice::from_raw_parts(
                 self.chip8_pixels.as_ptr() as *const u8,
@@ -312,34 +331,286 @@ impl Renderer {
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("command_encoder"),
             });
+        self.draw(&mut encoder, &view);
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("render_pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.3,
-                            g: 0.2,
-                            b: 0.1,
-                            a: 1.0,
-                        }),
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: None,
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+        Ok(())
+    }
+
+    // Draws the last rendered frame again into an offscreen texture the size
+    // of the surface and reads it back as tightly packed RGBA8 rows. Assumes
+    // an 8-bit RGBA or BGRA surface format, which is what surfaces offer in
+    // practice.
+    pub fn capture_frame(&self) -> Vec<u8> {
+        let (width, height) = (self.surface_config.width, self.surface_config.height);
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.surface_config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            label: Some("capture_texture"),
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Buffer rows must be padded to COPY_BYTES_PER_ROW_ALIGNMENT.
+        let row_bytes = width * std::mem::size_of::<u32>() as u32;
+        let padded_row_bytes = row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("capture_buffer"),
+            size: (padded_row_bytes * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("capture_encoder"),
             });
+        self.draw(&mut encoder, &view);
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(padded_row_bytes),
+                    rows_per_image: std::num::NonZeroU32::new(height),
+                },
+            },
+            size,
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::Maintain::Wait);
+
+        let bgra = matches!(
+            self.surface_config.format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        let rgba = unpad_rgba(
+            &slice.get_mapped_range(),
+            row_bytes as usize,
+            padded_row_bytes as usize,
+            bgra,
+        );
+        buffer.unmap();
+        rgba
+    }
+
+    fn draw(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear_color(self.palette.bg_color)),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.chip8_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..6, 0..1);
+    }
+}
+
+// Two triangles of (x, y, u, v) vertices covering the largest 2:1 area that
+// fits the surface, centered so the rest is letterboxed or pillarboxed. With
+// `integer_scale` each CHIP-8 pixel covers a whole number of surface pixels.
+fn quad_vertices(surface_size: winit::dpi::PhysicalSize<u32>, integer_scale: bool) -> [f32; 24] {
+    let width = surface_size.width.max(1) as f32;
+    let height = surface_size.height.max(1) as f32;
+    let mut scale =
+        (width / chip8::DISPLAY_WIDTH as f32).min(height / chip8::DISPLAY_HEIGHT as f32);
+    if integer_scale && scale >= 1.0 {
+        scale = scale.floor();
+    }
+    let x = chip8::DISPLAY_WIDTH as f32 * scale / width;
+    let y = chip8::DISPLAY_HEIGHT as f32 * scale / height;
+
+    #[rustfmt::skip]
+    let vertices = [
+        -x, -y, 0.0, 1.0,
+         x, -y, 1.0, 1.0,
+         x,  y, 1.0, 0.0,
+         x,  y, 1.0, 0.0,
+        -x,  y, 0.0, 0.0,
+        -x, -y, 0.0, 1.0,
+    ];
+    vertices
+}
+
+// Fifo is vsync and always supported. Without vsync, prefer Mailbox, which
+// does not tear, over Immediate. With no preference the adapter's first
+// mode is used.
+fn present_mode(available: &[wgpu::PresentMode], vsync: Option<bool>) -> wgpu::PresentMode {
+    let preferred: &[wgpu::PresentMode] = match vsync {
+        None => &[],
+        Some(true) => &[wgpu::PresentMode::Fifo],
+        Some(false) => &[wgpu::PresentMode::Mailbox, wgpu::PresentMode::Immediate],
+    };
+    preferred
+        .iter()
+        .copied()
+        .find(|mode| available.contains(mode))
+        .unwrap_or(available[0])
+}
 
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_bind_group(0, &self.chip8_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.draw(0..6, 0..1);
+// Drops the padding at the end of each buffer row and, for BGRA surfaces,
+// swaps the texels into RGBA order.
+fn unpad_rgba(mapped: &[u8], row_bytes: usize, padded_row_bytes: usize, bgra: bool) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(mapped.len() / padded_row_bytes * row_bytes);
+    for row in mapped.chunks(padded_row_bytes) {
+        rgba.extend_from_slice(&row[..row_bytes]);
+    }
+    if bgra {
+        for texel in rgba.chunks_mut(4) {
+            texel.swap(0, 2);
         }
+    }
+    rgba
+}
 
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
-        Ok(())
+fn vertex_bytes(vertices: &[f32]) -> &[u8] {
+    unsafe {
+        std::slice::from_raw_parts(
+            vertices.as_ptr() as *const u8,
+            std::mem::size_of_val(vertices),
+        )
+    }
+}
+
+// Texels are RGBA bytes in sRGB space, while clear colors are given in linear
+// space, so convert to make the letterbox match the texture's background.
+fn clear_color(texel: u32) -> wgpu::Color {
+    let [r, g, b, a] = texel.to_ne_bytes();
+    wgpu::Color {
+        r: srgb_to_linear(r),
+        g: srgb_to_linear(g),
+        b: srgb_to_linear(b),
+        a: a as f64 / 255.0,
+    }
+}
+
+fn srgb_to_linear(c: u8) -> f64 {
+    let c = c as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpad_rgba_drops_row_padding() {
+        // Two rows of one texel each, padded to 8 bytes.
+        let mapped = [1, 2, 3, 4, 0, 0, 0, 0, 5, 6, 7, 8, 0, 0, 0, 0];
+        assert_eq!(unpad_rgba(&mapped, 4, 8, false), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(unpad_rgba(&mapped, 4, 8, true), [3, 2, 1, 4, 7, 6, 5, 8]);
+    }
+
+    #[test]
+    fn clear_color_converts_srgb_texels_to_linear() {
+        let black = clear_color(chip8_util::rgba(0x00, 0x00, 0x00, 0xFF));
+        assert_eq!((black.r, black.g, black.b, black.a), (0.0, 0.0, 0.0, 1.0));
+
+        let color = clear_color(chip8_util::rgba(0xFF, 0x80, 0x00, 0x00));
+        assert_eq!((color.r, color.b, color.a), (1.0, 0.0, 0.0));
+        // sRGB 0x80 is about 21.6% linear intensity, not 50%.
+        assert!((color.g - 0.2158).abs() < 1e-4, "{}", color.g);
+    }
+
+    #[test]
+    fn present_mode_prefers_what_vsync_asks_for() {
+        use wgpu::PresentMode::*;
+
+        let all = [Fifo, Mailbox, Immediate];
+        assert_eq!(present_mode(&all, None), Fifo);
+        assert_eq!(present_mode(&all, Some(true)), Fifo);
+        assert_eq!(present_mode(&all, Some(false)), Mailbox);
+        assert_eq!(present_mode(&[Immediate, Fifo], Some(false)), Immediate);
+        // Without a match it falls back to the surface's first mode.
+        assert_eq!(present_mode(&[Fifo], Some(false)), Fifo);
+        assert_eq!(present_mode(&[Mailbox, Immediate], Some(true)), Mailbox);
+    }
+
+    // Half-extents of the quad in clip space, read off its top-right corner.
+    fn extents(width: u32, height: u32, integer_scale: bool) -> (f32, f32) {
+        let vertices = quad_vertices(winit::dpi::PhysicalSize::new(width, height), integer_scale);
+        (vertices[8], vertices[9])
+    }
+
+    #[test]
+    fn quad_vertices_keep_the_aspect_ratio() {
+        assert_eq!(extents(128, 64, false), (1.0, 1.0));
+        // Wide windows are pillarboxed, tall ones letterboxed.
+        assert_eq!(extents(1280, 320, false), (0.5, 1.0));
+        assert_eq!(extents(640, 640, false), (1.0, 0.5));
+        // The texture always spans the whole quad.
+        let vertices = quad_vertices(winit::dpi::PhysicalSize::new(1280, 320), false);
+        let uvs: Vec<_> = vertices.chunks(4).map(|v| (v[2], v[3])).collect();
+        assert_eq!(
+            uvs,
+            [
+                (0.0, 1.0),
+                (1.0, 1.0),
+                (1.0, 0.0),
+                (1.0, 0.0),
+                (0.0, 0.0),
+                (0.0, 1.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn quad_vertices_round_down_to_an_integer_scale() {
+        // 200x100 fits 3.125x; the integer scale uses 3x, 192x96.
+        assert_eq!(extents(200, 100, false), (1.0, 1.0));
+        assert_eq!(extents(200, 100, true), (0.96, 0.96));
+        // Below 1x there is no whole scale to round to.
+        assert_eq!(extents(32, 16, true), (1.0, 1.0));
+    }
+
+    #[test]
+    fn palette_fill_uses_the_latest_colors() {
+        let display = [true, false, false, true];
+        let mut texels = [0; 4];
+        let mut palette = Palette {
+            fg_color: 1,
+            bg_color: 2,
+        };
+        palette.fill(&mut texels, &display);
+        assert_eq!(texels, [1, 2, 2, 1]);
+
+        palette.set(3, 4);
+        assert_eq!((palette.fg_color, palette.bg_color), (3, 4));
+        palette.fill(&mut texels, &display);
+        assert_eq!(texels, [3, 4, 4, 3]);
     }
 }
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+const MAX_CYCLES: usize = 1000;
+
+fuzz_target!(|rom: &[u8]| {
+    let mut chip8 = chip8::Chip8::new();
+    if chip8.load_from_bytes(rom).is_err() {
+        return;
+    }
+    for _ in 0..MAX_CYCLES {
+        if chip8.cycle().is_err() {
+            break;
+        }
+    }
+});
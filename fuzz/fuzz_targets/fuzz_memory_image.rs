@@ -0,0 +1,24 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+const MEMORY_SIZE: usize = 0x1000;
+const MAX_CYCLES: usize = 1000;
+
+// The first two bytes pick the starting pc, the rest fill memory from 0x000 so
+// every address, including the font area, holds arbitrary data.
+fuzz_target!(|data: &[u8]| {
+    let Some((pc, data)) = data.split_first_chunk::<2>() else {
+        return;
+    };
+    let mut image = [0; MEMORY_SIZE];
+    let len = data.len().min(MEMORY_SIZE);
+    image[..len].copy_from_slice(&data[..len]);
+
+    let mut chip8 = chip8::Chip8::from_memory_image(&image, u16::from_be_bytes(*pc));
+    for _ in 0..MAX_CYCLES {
+        if chip8.cycle().is_err() {
+            break;
+        }
+    }
+});